@@ -0,0 +1,280 @@
+use std::ops::Div;
+
+const WORD: usize = u64::BITS as usize;
+
+// Packed boolean grid, one bit per cell
+// Every row starts on a fresh word so row scans never straddle rows,
+// and the padding bits past width are always kept at zero
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        let words_per_row = width.div_ceil(WORD);
+        Self {
+            width,
+            height,
+            words_per_row,
+            bits: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn index(&self, x: usize, y: usize) -> (usize, u64) {
+        assert!(
+            x < self.width && y < self.height,
+            "({}, {}) out of bounds",
+            x,
+            y
+        );
+        (y * self.words_per_row + x.div(WORD), 1 << (x % WORD))
+    }
+
+    fn row(&self, y: usize) -> &[u64] {
+        &self.bits[y * self.words_per_row..(y + 1) * self.words_per_row]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize) {
+        let (word, mask) = self.index(x, y);
+        self.bits[word] |= mask;
+    }
+
+    pub fn clear(&mut self, x: usize, y: usize) {
+        let (word, mask) = self.index(x, y);
+        self.bits[word] &= !mask;
+    }
+
+    pub fn test(&self, x: usize, y: usize) -> bool {
+        let (word, mask) = self.index(x, y);
+        self.bits[word] & mask != 0
+    }
+
+    // Unsets every cell, keeps the allocation
+    pub fn clear_all(&mut self) {
+        self.bits.fill(0);
+    }
+
+    pub fn count(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn row_count(&self, y: usize) -> usize {
+        self.row(y).iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    // x coordinates of the set cells on row y, left to right
+    pub fn row_ones(&self, y: usize) -> impl Iterator<Item = usize> + '_ {
+        self.row(y).iter().enumerate().flat_map(|(i, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(i * WORD + bit)
+            })
+        })
+    }
+
+    pub fn union_with(&mut self, other: &BitGrid) {
+        self.check_shape(other);
+        for (a, b) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *a |= b;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitGrid) {
+        self.check_shape(other);
+        for (a, b) in self.bits.iter_mut().zip(other.bits.iter()) {
+            *a &= b;
+        }
+    }
+
+    fn check_shape(&self, other: &BitGrid) {
+        assert!(
+            self.width == other.width && self.height == other.height,
+            "grid sizes differ: {}x{} vs {}x{}",
+            self.width,
+            self.height,
+            other.width,
+            other.height
+        );
+    }
+
+    // Longest run of set cells on row y
+    // Jumps over whole runs with trailing_ones/trailing_zeros instead of testing bit by bit
+    pub fn longest_run_in_row(&self, y: usize) -> usize {
+        let mut best: usize = 0;
+        let mut run: usize = 0;
+        for &word in self.row(y) {
+            if word == u64::MAX {
+                run += WORD;
+                continue;
+            }
+            let mut word = word;
+            let mut consumed: usize = 0;
+            while consumed < WORD {
+                let ones = word.trailing_ones() as usize;
+                run += ones;
+                consumed += ones;
+                if consumed >= WORD {
+                    break;
+                }
+                best = best.max(run);
+                run = 0;
+                word >>= ones;
+                let zeros = word.trailing_zeros() as usize;
+                consumed += zeros;
+                word = word.checked_shr(zeros as u32).unwrap_or(0);
+            }
+        }
+        best.max(run)
+    }
+
+    pub fn longest_horizontal_run(&self) -> usize {
+        (0..self.height)
+            .map(|y| self.longest_run_in_row(y))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Set cells inside the width x height rectangle starting at (x, y)
+    pub fn count_in_rect(&self, x: usize, y: usize, width: usize, height: usize) -> usize {
+        assert!(
+            x + width <= self.width && y + height <= self.height,
+            "rectangle out of bounds"
+        );
+        if width == 0 {
+            return 0;
+        }
+        let (first, last) = (x.div(WORD), (x + width - 1).div(WORD));
+        let first_mask = u64::MAX << (x % WORD);
+        let last_mask = u64::MAX >> (WORD - 1 - (x + width - 1) % WORD);
+
+        let mut count: usize = 0;
+        for row in y..y + height {
+            let words = self.row(row);
+            for (i, &word) in words.iter().enumerate().take(last + 1).skip(first) {
+                let mut mask = u64::MAX;
+                if i == first {
+                    mask &= first_mask;
+                }
+                if i == last {
+                    mask &= last_mask;
+                }
+                count += (word & mask).count_ones() as usize;
+            }
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Grid and the same cells as plain bools, filled from a fixed seed
+    fn random(width: usize, height: usize, seed: u64) -> (BitGrid, Vec<Vec<bool>>) {
+        let mut seed = seed;
+        let mut grid = BitGrid::new(width, height);
+        let mut naive = vec![vec![false; width]; height];
+        for (y, row) in naive.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                // Mostly set, so runs get long enough to cross words
+                if !seed.is_multiple_of(8) {
+                    grid.set(x, y);
+                    *cell = true;
+                }
+            }
+        }
+        (grid, naive)
+    }
+
+    fn naive_longest_run(row: &[bool]) -> usize {
+        row.split(|&cell| !cell)
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn runs_across_word_boundaries() {
+        for width in [63, 64, 65, 128] {
+            let mut grid = BitGrid::new(width, 3);
+            for x in 0..width {
+                grid.set(x, 0);
+            }
+            assert_eq!(grid.longest_run_in_row(0), width);
+            // Runs up to the last cell, just before the padding bits
+            for x in width - 10..width {
+                grid.set(x, 1);
+            }
+            // Inside the first word
+            grid.set(60, 2);
+            grid.set(61, 2);
+            assert_eq!(grid.longest_run_in_row(1), 10);
+            assert_eq!(grid.longest_run_in_row(2), 2);
+            assert_eq!(grid.longest_horizontal_run(), width);
+        }
+        // From the end of one word into the next
+        let mut grid = BitGrid::new(128, 1);
+        for x in 60..70 {
+            grid.set(x, 0);
+        }
+        assert_eq!(grid.longest_run_in_row(0), 10);
+    }
+
+    #[test]
+    fn runs_match_a_naive_scan() {
+        for width in [63, 64, 65, 128] {
+            let (grid, naive) = random(width, 40, width as u64);
+            for (y, row) in naive.iter().enumerate() {
+                assert_eq!(
+                    grid.longest_run_in_row(y),
+                    naive_longest_run(row),
+                    "row {}",
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rectangles_starting_and_ending_mid_word() {
+        for width in [63, 64, 65, 128] {
+            let (grid, naive) = random(width, 3, 7 * width as u64);
+            for x in 0..width {
+                for w in 0..=width - x {
+                    let expected: usize = naive[1..3]
+                        .iter()
+                        .map(|row| row[x..x + w].iter().filter(|&&cell| cell).count())
+                        .sum();
+                    assert_eq!(
+                        grid.count_in_rect(x, 1, w, 2),
+                        expected,
+                        "x {} width {}",
+                        x,
+                        w
+                    );
+                }
+            }
+            assert_eq!(grid.count_in_rect(0, 0, width, 3), grid.count());
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use day1::{Map, Row};
+
+// Rows shaped like the puzzle input, five digit ids with plenty of repeats
+fn generate(rows: usize) -> Map {
+    let mut map = Map::default();
+    let mut seed: u64 = 0x2024_1201;
+    let mut next = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        10000 + (seed % 90000) as i32
+    };
+    for _ in 0..rows {
+        map.add(Row {
+            values: vec![next(), next()],
+        })
+        .unwrap();
+    }
+    map.sort();
+    map
+}
+
+// Time per element should stay flat as the lists grow
+fn similarity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("similarity");
+    for rows in [10_000, 100_000, 1_000_000] {
+        let map = generate(rows);
+        group.throughput(Throughput::Elements(rows as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(rows), &map, |b, map| {
+            b.iter(|| map.similarity())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, similarity);
+criterion_main!(benches);
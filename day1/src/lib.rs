@@ -0,0 +1,386 @@
+use std::cell::OnceCell;
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+// How the entries of two columns are matched up before measuring
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pairing {
+    // Smallest with smallest, second smallest with second smallest...
+    Sorted,
+    // Entries on the same input line
+    Positional,
+}
+
+// What is measured over the matched pairs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    // Sum of |a - b|
+    Absolute,
+    // Sum of (a - b)^2, a single square is already too large for i64 with values
+    // near both ends of i32, so every metric is measured in i128
+    Squared,
+    // Largest |a - b|
+    MaxDeviation,
+    // Number of pairs where a != b
+    Mismatches,
+}
+
+impl Metric {
+    fn measure(&self, pairs: impl Iterator<Item = (i32, i32)>) -> i128 {
+        let deltas = pairs.map(|(a, b)| (a as i128 - b as i128).abs());
+        match self {
+            Metric::Absolute => deltas.sum(),
+            Metric::Squared => deltas.map(|d| d * d).sum(),
+            Metric::MaxDeviation => deltas.max().unwrap_or(0),
+            Metric::Mismatches => deltas.filter(|&d| d != 0).count() as i128,
+        }
+    }
+}
+
+impl FromStr for Pairing {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sorted" => Ok(Pairing::Sorted),
+            "positional" => Ok(Pairing::Positional),
+            _ => Err(format!(
+                "unknown pairing {:?}, expected sorted or positional",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absolute" => Ok(Metric::Absolute),
+            "squared" => Ok(Metric::Squared),
+            "max" => Ok(Metric::MaxDeviation),
+            "mismatches" => Ok(Metric::Mismatches),
+            _ => Err(format!(
+                "unknown metric {:?}, expected absolute, squared, max or mismatches",
+                s
+            )),
+        }
+    }
+}
+
+// Result of comparing two columns, indices start from 0
+#[derive(Debug, PartialEq, Eq)]
+pub struct PairReport {
+    pub left: usize,
+    pub right: usize,
+    pub value: i128,
+}
+
+#[derive(Debug, Default)]
+pub struct Map {
+    // Columns in input order
+    columns: Vec<Vec<i32>>,
+    // Sorted copies of the columns, made on first use and dropped when a row is added
+    sorted: OnceCell<Vec<Vec<i32>>>,
+}
+
+impl Map {
+    // The first row decides how many columns the map has
+    pub fn add(&mut self, row: Row) -> Result<(), ParseRowError> {
+        if self.columns.is_empty() {
+            self.columns = vec![Vec::new(); row.values.len()];
+        } else if row.values.len() != self.columns.len() {
+            return Err(ParseRowError::Columns {
+                expected: self.columns.len(),
+                found: row.values.len(),
+            });
+        }
+        for (column, value) in self.columns.iter_mut().zip(row.values) {
+            column.push(value);
+        }
+        self.sorted.take();
+        Ok(())
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns.len()
+    }
+
+    // Anything that needs sorted columns sorts them when first asked,
+    // this only does it up front, for example to keep it out of a timing
+    pub fn sort(&self) {
+        self.sorted_columns();
+    }
+
+    fn sorted_columns(&self) -> &[Vec<i32>] {
+        self.sorted.get_or_init(|| {
+            let mut sorted = self.columns.clone();
+            for column in sorted.iter_mut() {
+                column.sort();
+            }
+            sorted
+        })
+    }
+
+    fn sorted(&self, column: usize) -> &[i32] {
+        &self.sorted_columns()[column]
+    }
+
+    pub fn measure(&self, left: usize, right: usize, pairing: Pairing, metric: Metric) -> i128 {
+        let (a, b) = match pairing {
+            Pairing::Sorted => (self.sorted(left), self.sorted(right)),
+            Pairing::Positional => (&self.columns[left][..], &self.columns[right][..]),
+        };
+        metric.measure(a.iter().copied().zip(b.iter().copied()))
+    }
+
+    // Every column against every column to its right
+    pub fn compare(&self, pairing: Pairing, metric: Metric) -> Vec<PairReport> {
+        let mut reports = Vec::new();
+        for left in 0..self.columns.len() {
+            for right in left + 1..self.columns.len() {
+                reports.push(PairReport {
+                    left,
+                    right,
+                    value: self.measure(left, right, pairing, metric),
+                });
+            }
+        }
+        reports
+    }
+
+    // Puzzle part 1, the first two columns paired in sorted order
+    pub fn distance(&self) -> i128 {
+        self.measure(0, 1, Pairing::Sorted, Metric::Absolute)
+    }
+
+    // Puzzle part 2 over the first two columns
+    pub fn similarity(&self) -> i64 {
+        self.similarity_between(0, 1)
+    }
+
+    // Merge join over the sorted columns
+    // Each run of equal values is visited once on both sides, which keeps this O(n)
+    pub fn similarity_between(&self, left: usize, right: usize) -> i64 {
+        let (lhs, rhs) = (self.sorted(left), self.sorted(right));
+        let mut similarity: i64 = 0;
+        let (mut i, mut k) = (0, 0);
+        while i < lhs.len() && k < rhs.len() {
+            let (left, right) = (lhs[i], rhs[k]);
+            if left < right {
+                i += 1;
+            } else if left > right {
+                k += 1;
+            } else {
+                let left_count = lhs[i..].iter().take_while(|&&x| x == left).count();
+                let right_count = rhs[k..].iter().take_while(|&&x| x == right).count();
+                similarity += left as i64 * left_count as i64 * right_count as i64;
+                i += left_count;
+                k += right_count;
+            }
+        }
+        similarity
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Row {
+    pub values: Vec<i32>,
+}
+
+impl Row {
+    pub fn new(row: &str) -> Result<Row, ParseRowError> {
+        let values = row
+            .split_whitespace()
+            .map(|x| {
+                x.parse::<i32>()
+                    .map_err(|e| ParseRowError::Number(x.to_string(), e))
+            })
+            .collect::<Result<Vec<i32>, _>>()?;
+        if values.is_empty() {
+            return Err(ParseRowError::Empty);
+        }
+        Ok(Row { values })
+    }
+}
+
+impl fmt::Display for Row {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values: Vec<String> = self.values.iter().map(|x| x.to_string()).collect();
+        write!(f, "{}", values.join(","))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseRowError {
+    Empty,
+    // Row length differs from the rows before it
+    Columns { expected: usize, found: usize },
+    Number(String, ParseIntError),
+}
+
+impl fmt::Display for ParseRowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRowError::Empty => write!(f, "empty row"),
+            ParseRowError::Columns { expected, found } => {
+                write!(f, "expected {} columns, found {}", expected, found)
+            }
+            ParseRowError::Number(x, e) => write!(f, "invalid number {:?}: {}", x, e),
+        }
+    }
+}
+
+impl Error for ParseRowError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+
+    fn example() -> Map {
+        let mut map = Map::default();
+        for line in EXAMPLE.lines() {
+            map.add(Row::new(line).unwrap()).unwrap();
+        }
+        map
+    }
+
+    #[test]
+    fn distance_of_example() {
+        assert_eq!(example().distance(), 11);
+    }
+
+    #[test]
+    fn similarity_of_example() {
+        let map = example();
+        map.sort();
+        assert_eq!(map.similarity(), 31);
+    }
+
+    #[test]
+    fn rows_added_after_sorting_are_counted() {
+        let mut map = example();
+        assert_eq!(map.distance(), 11);
+        map.add(Row::new("10   3").unwrap()).unwrap();
+        // Sorted: [1, 2, 3, 3, 3, 4, 10] and [3, 3, 3, 3, 4, 5, 9]
+        assert_eq!(map.distance(), 6);
+        assert_eq!(map.similarity(), 40);
+    }
+
+    #[test]
+    fn nothing_to_compare_without_two_columns() {
+        assert_eq!(
+            Map::default().compare(Pairing::Sorted, Metric::Absolute),
+            vec![]
+        );
+        let mut map = Map::default();
+        map.add(Row::new("3").unwrap()).unwrap();
+        assert_eq!(map.columns(), 1);
+        assert_eq!(map.compare(Pairing::Sorted, Metric::Absolute), vec![]);
+    }
+
+    #[test]
+    fn similarity_matches_nested_loop() {
+        let mut map = Map::default();
+        for i in 0..500 {
+            map.add(Row {
+                values: vec![(i * 7919) % 61, (i * 104729) % 53],
+            })
+            .unwrap();
+        }
+        let mut expected: i64 = 0;
+        for left in map.columns[0].iter() {
+            let count = map.columns[1].iter().filter(|&right| left == right).count();
+            expected += *left as i64 * count as i64;
+        }
+        map.sort();
+        assert_eq!(map.similarity(), expected);
+    }
+
+    #[test]
+    fn metrics_per_column_pair() {
+        let mut map = Map::default();
+        for line in ["1 4 1", "5 2 1", "3 3 3"] {
+            map.add(Row::new(line).unwrap()).unwrap();
+        }
+        map.sort();
+
+        let values = |pairing, metric| -> Vec<i128> {
+            map.compare(pairing, metric)
+                .iter()
+                .map(|report| report.value)
+                .collect()
+        };
+        // Columns sorted: [1, 3, 5], [2, 3, 4], [1, 1, 3]
+        assert_eq!(values(Pairing::Sorted, Metric::Absolute), vec![2, 4, 4]);
+        assert_eq!(values(Pairing::Sorted, Metric::Squared), vec![2, 8, 6]);
+        assert_eq!(values(Pairing::Sorted, Metric::MaxDeviation), vec![1, 2, 2]);
+        assert_eq!(values(Pairing::Sorted, Metric::Mismatches), vec![2, 2, 3]);
+        // Columns as given: [1, 5, 3], [4, 2, 3], [1, 1, 3]
+        assert_eq!(values(Pairing::Positional, Metric::Absolute), vec![6, 4, 4]);
+        assert_eq!(
+            values(Pairing::Positional, Metric::Mismatches),
+            vec![2, 1, 2]
+        );
+
+        let pairs: Vec<(usize, usize)> = map
+            .compare(Pairing::Sorted, Metric::Absolute)
+            .iter()
+            .map(|report| (report.left, report.right))
+            .collect();
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (1, 2)]);
+    }
+
+    #[test]
+    fn squares_do_not_overflow() {
+        let measure = |rows: &[&str]| {
+            let mut map = Map::default();
+            for row in rows {
+                map.add(Row::new(row).unwrap()).unwrap();
+            }
+            map.measure(0, 1, Pairing::Positional, Metric::Squared)
+        };
+        assert_eq!(
+            measure(&["-1500000000 1500000000"]),
+            9_000_000_000_000_000_000
+        );
+        assert_eq!(
+            measure(&["-1500000000 1500000000", "0 1"]),
+            9_000_000_000_000_000_001
+        );
+        // One square too large for i64, and two that only overflow it when added up
+        assert_eq!(
+            measure(&["-2147483648 2147483647"]),
+            18_446_744_065_119_617_025
+        );
+        assert_eq!(
+            measure(&["-1500000000 1500000000", "1500000000 -1500000000"]),
+            18_000_000_000_000_000_000
+        );
+    }
+
+    #[test]
+    fn row_errors() {
+        assert_eq!(Row::new("1   2"), Ok(Row { values: vec![1, 2] }));
+        assert_eq!(Row::new(""), Err(ParseRowError::Empty));
+        assert!(matches!(
+            Row::new("1 x"),
+            Err(ParseRowError::Number(x, _)) if x == "x"
+        ));
+
+        let mut map = Map::default();
+        map.add(Row::new("1 2").unwrap()).unwrap();
+        assert_eq!(
+            map.add(Row::new("1 2 3").unwrap()),
+            Err(ParseRowError::Columns {
+                expected: 2,
+                found: 3
+            })
+        );
+    }
+}
@@ -1,111 +1,86 @@
+use day1::{Map, Metric, Pairing, Row};
 use std::env;
-use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-pub struct Map {
-    left: Vec<i32>,
-    right: Vec<i32>,
+fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+where
+    P: AsRef<Path>,
+{
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines())
 }
 
-impl Default for Map {
-    fn default() -> Self {
-        Map {
-            left: Vec::new(),
-            right: Vec::new(),
-        }
-    }
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} <filename> [--pairing sorted|positional] [--metric absolute|squared|max|mismatches]",
+        program
+    );
+    std::process::exit(1);
 }
 
-impl Map {
-    fn add(&mut self, row: Row) -> () {
-        self.left.push(row.left);
-        self.right.push(row.right);
-    }
-
-    fn sort(&mut self) -> () {
-        self.left.sort();
-        self.right.sort();
-    }
+fn main() {
+    let args: Vec<String> = env::args().collect();
 
-    fn sum(&mut self) -> i32 {
-        let mut sum: i32 = 0;
-        for (left, right) in self.left.iter().zip(self.right.iter()) {
-            let partsum: i32 = (left - right).into();
-            sum = sum + <i32 as Into<i32>>::into(partsum.abs());
-        }
-        sum
+    if args.len() < 2 {
+        usage(&args[0]);
     }
+    let filename = &args[1];
 
-    fn similarity(&mut self) -> i32 {
-        let mut similarity: i32 = 0;
-        for left in self.left.iter() {
-            let mut count: i32 = 0;
-            for right in self.right.iter() {
-                if left == right {
-                    count += 1;
-                }
-            }
-            similarity += left * count;
+    let mut pairing: Option<Pairing> = None;
+    let mut metric: Option<Metric> = None;
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        let value = options.next().unwrap_or_else(|| usage(&args[0]));
+        let parsed = match option.as_str() {
+            "--pairing" => value.parse().map(|p| pairing = Some(p)),
+            "--metric" => value.parse().map(|m| metric = Some(m)),
+            _ => usage(&args[0]),
+        };
+        if let Err(e) = parsed {
+            eprintln!("{}", e);
+            std::process::exit(1);
         }
-        similarity
     }
-}
 
-pub struct Row {
-    pub left: i32,
-    pub right: i32,
-}
+    let mut map = Map::default();
 
-impl Row {
-    fn new(row: String) -> Row {
-        let numbers: Vec<i32> = row
-            .split_whitespace()
-            .map(|x| x.parse::<i32>().unwrap())
-            .collect();
-        Row {
-            left: numbers[0],
-            right: numbers[1],
+    let lines = read_lines(filename).expect("Unable to read file");
+    for (i, line) in lines.map_while(Result::ok).enumerate() {
+        if let Err(e) = Row::new(&line).and_then(|row| map.add(row)) {
+            eprintln!("{}:{}: {}", filename, i + 1, e);
+            std::process::exit(1);
         }
     }
-}
 
-impl fmt::Display for Row {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{},{}", self.left, self.right)
+    if map.columns() < 2 {
+        eprintln!(
+            "{}: need at least two columns to compare, found {}",
+            filename,
+            map.columns()
+        );
+        std::process::exit(1);
     }
-}
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
+    // The puzzle itself, two columns and no options
+    if map.columns() == 2 && pairing.is_none() && metric.is_none() {
+        let distance = map.distance();
+        println!("Length: {}", distance);
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let filename = &args[1];
-
-    let mut map = Vec::new();
-
-    if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
-            let numbers: Vec<i32> = line
-                .split_whitespace()
-                .map(|x| x.parse::<i32>().unwrap())
-                .collect();
-
-            map.push(numbers);
-        }
+        let similarity = map.similarity();
+        println!("Similarity: {}", similarity);
+        return;
     }
 
-    map.sort();
-    let sum = map.sum();
-    println!("Length: {}", sum);
-
-    let similarity = map.similarity();
-    println!("Similarity: {}", similarity);
+    let pairing = pairing.unwrap_or(Pairing::Sorted);
+    let metric = metric.unwrap_or(Metric::Absolute);
+    for report in map.compare(pairing, metric) {
+        println!(
+            "Columns {}-{}: {}",
+            report.left + 1,
+            report.right + 1,
+            report.value
+        );
+    }
 }
@@ -1,102 +1,11 @@
+use day10::matrix::Matrix;
+use render::{Colour, Overlay, Style};
 use std::collections::HashSet;
 use std::env;
-use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::ops::{Div, Rem};
 use std::path::Path;
 
-#[derive(Clone, Debug, Default)]
-struct Matrix<T> {
-    cols: usize,
-    data: Vec<T>,
-}
-
-impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
-    pub fn new(cols: usize, data: Vec<T>) -> Self {
-        Self { cols, data }
-    }
-
-    pub fn get_mut(&mut self, pos: isize) -> Option<&mut T> {
-        if pos >= 0 && pos < self.data.len() as isize {
-            return Some(&mut self.data[pos as usize]);
-        } else {
-            return None;
-        }
-    }
-
-    pub fn get(&self, x: isize, y: isize) -> Option<&T> {
-        if self.check_boundaries(x, y) {
-            let pos = self.pos_from_coords(x.try_into().unwrap(), y.try_into().unwrap());
-            if pos >= self.data.len() {
-                return None;
-            }
-            return Some(&self.data[pos]);
-        }
-        None
-    }
-
-    fn check_boundaries(&self, x: isize, y: isize) -> bool {
-        if x >= 0
-            && y >= 0
-            && x < self.cols.try_into().unwrap()
-            && y < self.data.len().div(self.cols).try_into().unwrap()
-        {
-            return true;
-        } else {
-            return false;
-        }
-    }
-
-    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
-        let asd = &self.cols * y + x;
-        //println!("asd: {}", &asd);
-        return asd;
-    }
-
-    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
-        let x = pos.rem(&self.cols);
-        let y = pos.div(&self.cols);
-        return (x, y);
-    }
-
-    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
-        let x = pos.rem(&self.cols) as isize;
-        let y = pos.div(&self.cols) as isize;
-        return (x, y);
-    }
-}
-
-impl fmt::Display for Matrix<u32> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 && i >= self.cols {
-                write!(f, "\n")?;
-            }
-            write!(f, "{} ", c)?;
-        }
-        Ok(())
-    }
-}
-
-impl Matrix<u32> {
-    fn dbg(&self, pos: usize, dir: usize) -> () {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 {
-                println!("");
-            }
-            if i == pos {
-                print!("[{}]", c);
-            } else if i == dir {
-                print!("<{}>", c);
-            } else {
-                print!(" {} ", c);
-            }
-        }
-        println!("");
-    }
-}
-
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
 where
     P: AsRef<Path>,
@@ -109,7 +18,7 @@ fn traverse(
     pos: usize,
     map: &Matrix<u32>,
     i: u32,
-    directions: &Vec<(isize, isize)>,
+    directions: &[(isize, isize)],
     visited: &mut HashSet<usize>,
 ) -> usize {
     let (x, y) = map.icoords_from_pos(pos);
@@ -120,12 +29,8 @@ fn traverse(
         match map.get(new_x, new_y) {
             Some(next_val) if *next_val == 9 && i == 8 => {
                 let new_pos = map.pos_from_coords(new_x as usize, new_y as usize);
-                match visited.contains(&new_pos) {
-                    false => {
-                        visited.insert(new_pos);
-                        path_count += 1;
-                    }
-                    true => {}
+                if visited.insert(new_pos) {
+                    path_count += 1;
                 }
             }
             Some(next_val) if *next_val == i + 1 => {
@@ -136,10 +41,10 @@ fn traverse(
             None => {}
         }
     }
-    return path_count;
+    path_count
 }
 
-fn traverse2(pos: usize, map: &Matrix<u32>, i: u32, directions: &Vec<(isize, isize)>) -> usize {
+fn traverse2(pos: usize, map: &Matrix<u32>, i: u32, directions: &[(isize, isize)]) -> usize {
     let (x, y) = map.icoords_from_pos(pos);
     let mut path_count: usize = 0;
     for (xdir, ydir) in directions.iter() {
@@ -157,7 +62,7 @@ fn traverse2(pos: usize, map: &Matrix<u32>, i: u32, directions: &Vec<(isize, isi
             None => {}
         }
     }
-    return path_count;
+    path_count
 }
 
 fn main() {
@@ -168,7 +73,7 @@ fn main() {
     let mut cols: usize = 0;
     const RADIX: u32 = 10;
     if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
+        for line in lines.map_while(Result::ok) {
             let mut levels: Vec<u32> = line.chars().map(|x| x.to_digit(RADIX).unwrap()).collect();
             cols = levels.len();
             data.append(&mut levels);
@@ -178,15 +83,32 @@ fn main() {
     let map: Matrix<u32> = Matrix { data, cols };
     let directions = vec![(0, -1), (-1, 0), (0, 1), (1, 0)];
     let mut p1_sum: usize = 0;
+    let mut peaks: HashSet<usize> = HashSet::new();
 
     for (pos, point) in map.data.iter().enumerate() {
         let mut visited: HashSet<usize> = HashSet::new();
         if *point == 0 {
             let trailheads = traverse(pos, &map, 0, &directions, &mut visited);
             p1_sum += trailheads;
+            peaks.extend(visited);
         }
     }
 
+    if args.iter().any(|arg| arg == "--show") {
+        let trailheads = (0..map.data.len()).filter(|&pos| map.data[pos] == 0);
+        let overlay = Overlay::new(&map)
+            .highlight(
+                trailheads,
+                Style::default().brackets('[', ']').colour(Colour::Green),
+            )
+            .highlight(
+                peaks,
+                Style::default().brackets('<', '>').colour(Colour::Red),
+            )
+            .rulers();
+        print!("{}", overlay);
+    }
+
     println!("P1: {}", p1_sum);
 
     let mut p2_sum: usize = 0;
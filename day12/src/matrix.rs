@@ -0,0 +1,243 @@
+use std::fmt;
+use std::ops::{Div, Rem};
+
+#[derive(Clone, Debug, Default)]
+pub struct Matrix<T> {
+    pub cols: usize,
+    pub data: Vec<T>,
+}
+
+// Which cells count as touching when growing a component
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    fn directions(&self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(0, -1), (-1, 0), (0, 1), (1, 0)],
+            Connectivity::Eight => &[
+                (0, -1),
+                (-1, 0),
+                (0, 1),
+                (1, 0),
+                (-1, -1),
+                (1, -1),
+                (-1, 1),
+                (1, 1),
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Component {
+    pub size: usize,
+    // Bounding box corners as (x, y), both inclusive
+    pub min: (usize, usize),
+    pub max: (usize, usize),
+    pub cells: Vec<usize>,
+    // Cell sides facing another component or the edge of the grid
+    pub edges: usize,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Labels {
+    // Component index of every cell, same shape as the labelled matrix
+    pub grid: Matrix<usize>,
+    pub components: Vec<Component>,
+}
+
+impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
+    pub fn new(cols: usize, data: Vec<T>) -> Self {
+        Self { cols, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        if self.cols == 0 {
+            return 0;
+        }
+        self.data.len().div(self.cols)
+    }
+
+    pub fn get_pos(&self, pos: usize) -> Option<&T> {
+        self.data.get(pos)
+    }
+
+    pub fn get_pos_mut(&mut self, pos: usize) -> Option<&mut T> {
+        self.data.get_mut(pos)
+    }
+
+    pub fn get(&mut self, x: isize, y: isize) -> Option<&mut T> {
+        if self.check_boundaries(x, y) {
+            let pos = self.pos_from_coords(x as usize, y as usize);
+            return self.data.get_mut(pos);
+        }
+        None
+    }
+
+    pub fn check_boundaries(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && x < self.cols as isize && y < self.rows() as isize
+    }
+
+    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
+        self.cols * y + x
+    }
+
+    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
+        let x = pos.rem(self.cols);
+        let y = pos.div(self.cols);
+        (x, y)
+    }
+
+    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
+        let x = pos.rem(self.cols) as isize;
+        let y = pos.div(self.cols) as isize;
+        (x, y)
+    }
+
+    // Splits the matrix into connected components
+    // same(a, b) decides whether two touching cells belong together
+    pub fn label<F>(&self, same: F, connectivity: Connectivity) -> Labels
+    where
+        F: Fn(&T, &T) -> bool,
+    {
+        let mut grid = Matrix::new(self.cols, vec![usize::MAX; self.data.len()]);
+        let mut components: Vec<Component> = Vec::new();
+        let mut stack: Vec<usize> = Vec::new();
+
+        for start in 0..self.data.len() {
+            if grid.data[start] != usize::MAX {
+                continue;
+            }
+            let label = components.len();
+            let (x, y) = self.coords_from_pos(start);
+            let mut component = Component {
+                min: (x, y),
+                max: (x, y),
+                ..Default::default()
+            };
+
+            grid.data[start] = label;
+            stack.push(start);
+            while let Some(pos) = stack.pop() {
+                let (x, y) = self.coords_from_pos(pos);
+                component.size += 1;
+                component.cells.push(pos);
+                component.min = (component.min.0.min(x), component.min.1.min(y));
+                component.max = (component.max.0.max(x), component.max.1.max(y));
+
+                for (xdir, ydir) in connectivity.directions() {
+                    let new_x = x as isize + xdir;
+                    let new_y = y as isize + ydir;
+                    if !self.check_boundaries(new_x, new_y) {
+                        continue;
+                    }
+                    let new_pos = self.pos_from_coords(new_x as usize, new_y as usize);
+                    if grid.data[new_pos] == usize::MAX
+                        && same(&self.data[pos], &self.data[new_pos])
+                    {
+                        grid.data[new_pos] = label;
+                        stack.push(new_pos);
+                    }
+                }
+            }
+            component.cells.sort_unstable();
+            components.push(component);
+        }
+
+        // Edges are counted on the four sides of a cell whatever the connectivity
+        for (pos, &label) in grid.data.iter().enumerate() {
+            let (x, y) = grid.icoords_from_pos(pos);
+            for (xdir, ydir) in Connectivity::Four.directions() {
+                let (new_x, new_y) = (x + xdir, y + ydir);
+                if !grid.check_boundaries(new_x, new_y)
+                    || grid.data[grid.pos_from_coords(new_x as usize, new_y as usize)] != label
+                {
+                    components[label].edges += 1;
+                }
+            }
+        }
+
+        Labels { grid, components }
+    }
+}
+
+impl fmt::Display for Matrix<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.data.iter().enumerate() {
+            if i.rem(self.cols) == 0 && i >= self.cols {
+                writeln!(f)?;
+            }
+            write!(f, "{} ", c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &str) -> Matrix<char> {
+        let cols = rows.lines().next().map_or(0, |row| row.len());
+        Matrix::new(cols, rows.lines().flat_map(|row| row.chars()).collect())
+    }
+
+    #[test]
+    fn labels_the_example_regions() {
+        let garden = grid("AAAA\nBBCD\nBBCC\nEEEC");
+        let labels = garden.label(|a, b| a == b, Connectivity::Four);
+        assert_eq!(labels.components.len(), 5);
+        assert_eq!(
+            labels.grid.data,
+            vec![0, 0, 0, 0, 1, 1, 2, 3, 1, 1, 2, 2, 4, 4, 4, 2]
+        );
+
+        // Region, size, edges, bounding box and cells in reading order
+        let expected = [
+            ('A', 4, 10, (0, 0), (3, 0), vec![0, 1, 2, 3]),
+            ('B', 4, 8, (0, 1), (1, 2), vec![4, 5, 8, 9]),
+            ('C', 4, 10, (2, 1), (3, 3), vec![6, 10, 11, 15]),
+            ('D', 1, 4, (3, 1), (3, 1), vec![7]),
+            ('E', 3, 8, (0, 3), (2, 3), vec![12, 13, 14]),
+        ];
+        for (component, (plant, size, edges, min, max, cells)) in
+            labels.components.iter().zip(expected)
+        {
+            assert_eq!(garden.data[component.cells[0]], plant);
+            assert_eq!(
+                (
+                    component.size,
+                    component.edges,
+                    component.min,
+                    component.max
+                ),
+                (size, edges, min, max),
+                "region {}",
+                plant
+            );
+            assert_eq!(component.cells, cells);
+        }
+        let price: usize = labels.components.iter().map(|c| c.size * c.edges).sum();
+        assert_eq!(price, 140);
+    }
+
+    #[test]
+    fn eight_connectivity_joins_diagonals() {
+        let board = grid("XO\nOX");
+        let four = board.label(|a, b| a == b, Connectivity::Four);
+        assert_eq!(four.components.len(), 4);
+
+        let eight = board.label(|a, b| a == b, Connectivity::Eight);
+        assert_eq!(eight.components.len(), 2);
+        assert_eq!(eight.grid.data, vec![0, 1, 1, 0]);
+        let x = &eight.components[0];
+        assert_eq!((x.size, x.min, x.max), (2, (0, 0), (1, 1)));
+        assert_eq!(x.cells, vec![0, 3]);
+        // Edges still count the four sides, and no two X cells share one
+        assert_eq!(x.edges, 8);
+    }
+}
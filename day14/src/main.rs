@@ -1,3 +1,4 @@
+use bitgrid::BitGrid;
 use std::env;
 use std::fs::File;
 use std::io::{self, BufRead};
@@ -15,7 +16,7 @@ struct Robot {
 }
 
 impl Robot {
-    fn swarm(&mut self) -> () {
+    fn swarm(&mut self) {
         self.x = (self.x + self.vx).rem_euclid(WIDTH);
         self.y = (self.y + self.vy).rem_euclid(HEIGHT);
     }
@@ -48,7 +49,7 @@ fn parse_values(line: &str) -> Robot {
     Robot { x, y, vx, vy }
 }
 
-fn safety_factor(robots: &Vec<Robot>) -> usize {
+fn safety_factor(robots: &[Robot]) -> usize {
     let mut top_left: usize = 0;
     let mut top_right: usize = 0;
     let mut bot_left: usize = 0;
@@ -69,29 +70,17 @@ fn safety_factor(robots: &Vec<Robot>) -> usize {
         }
     }
 
-    return top_left * top_right * bot_left * bot_right;
+    top_left * top_right * bot_left * bot_right
 }
 
-fn find_the_tree(robots: &Vec<Robot>) -> bool {
-    let mut bathroom = vec![false; (WIDTH * HEIGHT) as usize];
+// Looks for the frame around the tree, a long unbroken row of robots
+fn find_the_tree(robots: &[Robot], bathroom: &mut BitGrid) -> bool {
+    bathroom.clear_all();
     for robot in robots {
-        let pos = robot.y * WIDTH + robot.x;
-        bathroom[pos as usize] = true;
+        bathroom.set(robot.x as usize, robot.y as usize);
     }
 
-    let mut robot_counter: usize = 0;
-    for robot in bathroom {
-        if robot_counter == 30 {
-            return true;
-        }
-        if robot {
-            robot_counter += 1;
-        } else {
-            robot_counter = 0;
-        }
-    }
-
-    false
+    bathroom.longest_horizontal_run() >= 30
 }
 
 fn main() {
@@ -101,7 +90,7 @@ fn main() {
     let mut robots: Vec<Robot> = Vec::new();
 
     if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
+        for line in lines.map_while(Result::ok) {
             let robot = parse_values(&line);
             robots.push(robot);
         }
@@ -117,13 +106,14 @@ fn main() {
     let p1 = safety_factor(&robots);
     println!("P1: {}", p1);
 
+    let mut bathroom = BitGrid::new(WIDTH as usize, HEIGHT as usize);
     let mut p2_answer: usize = 0;
     loop {
         for robot in p2_robots.iter_mut() {
             robot.swarm();
         }
         p2_answer += 1;
-        if find_the_tree(&p2_robots) {
+        if find_the_tree(&p2_robots, &mut bathroom) {
             println!("P2: {}", p2_answer);
             break;
         }
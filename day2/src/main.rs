@@ -1,24 +1,324 @@
 use std::env;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
 use std::path::Path;
 
-fn check_level(arr: &[i32]) -> bool {
-    let first_diff = arr[1] - arr[0];
-    let direction = match first_diff {
-        1..=3 => 1,
-        -3..=-1 => -1,
-        _ => return false,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Direction {
+    Increasing,
+    Decreasing,
+    Either,
+}
+
+// What counts as a safe report, the defaults are the puzzle rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Rules {
+    min_step: i32,
+    max_step: i32,
+    allow_plateaus: bool,
+    direction: Direction,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            min_step: 1,
+            max_step: 3,
+            allow_plateaus: false,
+            direction: Direction::Either,
+        }
+    }
+}
+
+impl Rules {
+    // Sets one rule by name, shared by the command line and the rules file
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number = |value: &str| {
+            value
+                .parse::<i32>()
+                .map_err(|e| format!("{}: invalid number {:?}: {}", key, value, e))
+        };
+        match key {
+            "min_step" => self.min_step = number(value)?,
+            "max_step" => self.max_step = number(value)?,
+            "allow_plateaus" => {
+                self.allow_plateaus = value
+                    .parse()
+                    .map_err(|_| format!("{}: expected true or false, got {:?}", key, value))?
+            }
+            "direction" => {
+                self.direction = match value {
+                    "increasing" => Direction::Increasing,
+                    "decreasing" => Direction::Decreasing,
+                    "either" => Direction::Either,
+                    _ => {
+                        return Err(format!(
+                            "{}: expected increasing, decreasing or either, got {:?}",
+                            key, value
+                        ))
+                    }
+                }
+            }
+            _ => return Err(format!("unknown rule {:?}", key)),
+        }
+        Ok(())
+    }
+
+    // One "key = value" per line, # starts a comment
+    fn load(&mut self, filename: &str) -> Result<(), String> {
+        let file = std::fs::read_to_string(filename).map_err(|e| format!("{}: {}", filename, e))?;
+        for (i, line) in file.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or(format!(
+                "{}:{}: expected key = value",
+                filename,
+                i + 1
+            ))?;
+            self.set(key.trim(), value.trim())
+                .map_err(|e| format!("{}:{}: {}", filename, i + 1, e))?;
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.min_step < 1 || self.min_step > self.max_step {
+            return Err(format!(
+                "step range {}..={} must start from at least 1 and not be empty",
+                self.min_step, self.max_step
+            ));
+        }
+        Ok(())
+    }
+
+    fn directions(&self) -> &'static [i32] {
+        match self.direction {
+            Direction::Increasing => &[1],
+            Direction::Decreasing => &[-1],
+            Direction::Either => &[1, -1],
+        }
+    }
+
+    fn step_ok(&self, direction: i32, a: i32, b: i32) -> bool {
+        let step = (b - a) * direction;
+        (step == 0 && self.allow_plateaus) || (self.min_step..=self.max_step).contains(&step)
+    }
+}
+
+// Fewest levels that have to be removed to make the report safe,
+// None if that takes more than k removals or the report has fewer than two levels.
+// best[i] is the fewest removals among arr[..=i] with arr[i] kept. The previous
+// kept level can only be up to k + 1 steps back, so this is O(n * k) with no copies
+fn min_removals(arr: &[i32], k: usize, rules: &Rules) -> Option<usize> {
+    if arr.len() < 2 {
+        return None;
+    }
+
+    let mut fewest: Option<usize> = None;
+    for &direction in rules.directions() {
+        let mut best: Vec<Option<usize>> = vec![None; arr.len()];
+        for i in 0..arr.len() {
+            // Drop everything before i
+            let mut removals = if i <= k { Some(i) } else { None };
+            for j in i.saturating_sub(k + 1)..i {
+                if let Some(before) = best[j] {
+                    let total = before + (i - j - 1);
+                    if total <= k && rules.step_ok(direction, arr[j], arr[i]) {
+                        removals = Some(removals.map_or(total, |r| r.min(total)));
+                    }
+                }
+            }
+            best[i] = removals;
+
+            // Drop everything after i
+            if let Some(removals) = removals {
+                let total = removals + (arr.len() - 1 - i);
+                if total <= k {
+                    fewest = Some(fewest.map_or(total, |f| f.min(total)));
+                }
+            }
+        }
+    }
+    fewest
+}
+
+fn check_level(arr: &[i32], k: usize, rules: &Rules) -> bool {
+    min_removals(arr, k, rules).is_some()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Reason {
+    DirectionFlip,
+    ZeroStep,
+    StepTooSmall,
+    StepTooLarge,
+    TooShort,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::DirectionFlip => write!(f, "direction flip"),
+            Reason::ZeroStep => write!(f, "zero step"),
+            Reason::StepTooSmall => write!(f, "step below minimum"),
+            Reason::StepTooLarge => write!(f, "step above maximum"),
+            Reason::TooShort => write!(f, "fewer than two levels"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Verdict {
+    SafeIncreasing,
+    SafeDecreasing,
+    // Only plateaus, when those are allowed
+    SafeFlat,
+    // Index of the level the dampener removes
+    SafeAfterRemoving(usize),
+    // index is the first level of the first bad pair
+    Unsafe { index: usize, reason: Reason },
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::SafeIncreasing => write!(f, "safe increasing"),
+            Verdict::SafeDecreasing => write!(f, "safe decreasing"),
+            Verdict::SafeFlat => write!(f, "safe flat"),
+            Verdict::SafeAfterRemoving(i) => write!(f, "safe after removing {}", i),
+            Verdict::Unsafe { index, reason } => {
+                write!(f, "unsafe at {}-{}: {}", index, index + 1, reason)
+            }
+        }
+    }
+}
+
+// Walks the report with the level at skip left out
+// Ok holds the direction of a safe report, Err the first bad pair and why
+fn first_violation(
+    arr: &[i32],
+    skip: Option<usize>,
+    rules: &Rules,
+) -> Result<i32, (usize, Reason)> {
+    let mut levels = arr
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| Some(*i) != skip)
+        .peekable();
+    let mut direction = match rules.direction {
+        Direction::Increasing => 1,
+        Direction::Decreasing => -1,
+        Direction::Either => 0,
     };
+    while let Some((i, &a)) = levels.next() {
+        let Some(&(_, &b)) = levels.peek() else {
+            break;
+        };
+        let step = b - a;
+        if step == 0 {
+            if rules.allow_plateaus {
+                continue;
+            }
+            return Err((i, Reason::ZeroStep));
+        }
+        if direction == 0 {
+            direction = step.signum();
+        }
+        if step.signum() != direction {
+            return Err((i, Reason::DirectionFlip));
+        }
+        if step.abs() < rules.min_step {
+            return Err((i, Reason::StepTooSmall));
+        }
+        if step.abs() > rules.max_step {
+            return Err((i, Reason::StepTooLarge));
+        }
+    }
+    Ok(direction)
+}
+
+// Same answer as check_level with one removal, but says why
+fn classify(arr: &[i32], rules: &Rules) -> Verdict {
+    if arr.len() < 2 {
+        return Verdict::Unsafe {
+            index: 0,
+            reason: Reason::TooShort,
+        };
+    }
+    match first_violation(arr, None, rules) {
+        Ok(1) => Verdict::SafeIncreasing,
+        Ok(-1) => Verdict::SafeDecreasing,
+        // No direction was ever set, so every pair was a plateau
+        Ok(_) if rules.allow_plateaus => Verdict::SafeFlat,
+        Ok(direction) => unreachable!("safe report with direction {}", direction),
+        Err((index, reason)) => {
+            match (0..arr.len()).find(|&i| first_violation(arr, Some(i), rules).is_ok()) {
+                Some(i) => Verdict::SafeAfterRemoving(i),
+                None => Verdict::Unsafe { index, reason },
+            }
+        }
+    }
+}
 
-    arr.iter()
-        .zip(arr.iter().skip(1))
-        .skip(1)
-        .all(|(&a, &b)| match (direction, b - a) {
-            (1, 1..=3) => true,
-            (-1, -3..=-1) => true,
-            _ => false,
-        })
+fn levels_str(report: &[i32]) -> String {
+    let levels: Vec<String> = report.iter().map(|x| x.to_string()).collect();
+    levels.join(" ")
+}
+
+// Fewest levels to remove with no limit on how many, removing all but one always
+// works once there are two levels to start from
+fn fewest_removals(report: &[i32], rules: &Rules) -> Option<usize> {
+    min_removals(report, report.len(), rules)
+}
+
+fn removals_str(report: &[i32], rules: &Rules) -> String {
+    fewest_removals(report, rules).map_or("-".to_string(), |r| r.to_string())
+}
+
+fn print_table(data: &[Vec<i32>], rules: &Rules) {
+    let width = data
+        .iter()
+        .map(|report| levels_str(report).len())
+        .max()
+        .unwrap_or(0)
+        .max("levels".len());
+    println!("{:>5}  {:<width$}  removals  verdict", "line", "levels");
+    for (i, report) in data.iter().enumerate() {
+        println!(
+            "{:>5}  {:<width$}  {:>8}  {}",
+            i + 1,
+            levels_str(report),
+            removals_str(report, rules),
+            classify(report, rules)
+        );
+    }
+}
+
+fn print_csv(data: &[Vec<i32>], rules: &Rules) {
+    println!("line,levels,verdict,index,reason,min_removals");
+    for (i, report) in data.iter().enumerate() {
+        let (verdict, index, reason) = match classify(report, rules) {
+            Verdict::SafeIncreasing => ("safe_increasing", String::new(), String::new()),
+            Verdict::SafeDecreasing => ("safe_decreasing", String::new(), String::new()),
+            Verdict::SafeFlat => ("safe_flat", String::new(), String::new()),
+            Verdict::SafeAfterRemoving(index) => {
+                ("safe_after_removing", index.to_string(), String::new())
+            }
+            Verdict::Unsafe { index, reason } => ("unsafe", index.to_string(), reason.to_string()),
+        };
+        println!(
+            "{},{},{},{},{},{}",
+            i + 1,
+            levels_str(report),
+            verdict,
+            index,
+            reason,
+            fewest_removals(report, rules).map_or(String::new(), |r| r.to_string())
+        );
+    }
 }
 
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
@@ -29,46 +329,412 @@ where
     Ok(io::BufReader::new(file).lines())
 }
 
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} <filename> [k] [--table] [--csv] [--rules file] [--min-step n] [--max-step n] [--plateaus] [--direction increasing|decreasing|either]",
+        program
+    );
+    std::process::exit(1);
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
     let filename = &args[1];
 
+    let mut table = false;
+    let mut csv = false;
+    let mut tolerance: Option<usize> = None;
+    let mut rules = Rules::default();
+
+    // Options are applied in order, so flags after --rules override the file
+    let mut options = args[2..].iter();
+    while let Some(option) = options.next() {
+        let mut value = || options.next().unwrap_or_else(|| usage(&args[0]));
+        let applied = match option.as_str() {
+            "--table" => {
+                table = true;
+                Ok(())
+            }
+            "--csv" => {
+                csv = true;
+                Ok(())
+            }
+            "--plateaus" => rules.set("allow_plateaus", "true"),
+            "--rules" => rules.load(value()),
+            "--min-step" => rules.set("min_step", value()),
+            "--max-step" => rules.set("max_step", value()),
+            "--direction" => rules.set("direction", value()),
+            k if !k.starts_with("--") => k
+                .parse()
+                .map(|k| tolerance = Some(k))
+                .map_err(|_| format!("Tolerance must be a number, got {:?}", k)),
+            _ => usage(&args[0]),
+        };
+        if let Err(e) = applied {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = rules.validate() {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+
     let mut data = Vec::new();
 
     if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
-            let levels: Vec<i32> = line
+        for (i, line) in lines.map_while(Result::ok).enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let levels: Result<Vec<i32>, _> = line
                 .split_whitespace()
-                .map(|x| x.parse::<i32>().unwrap())
+                .map(|x| {
+                    x.parse::<i32>()
+                        .map_err(|e| format!("invalid level {:?}: {}", x, e))
+                })
                 .collect();
-            data.push(levels);
+            match levels {
+                Ok(levels) => data.push(levels),
+                Err(e) => {
+                    eprintln!("{}:{}: {}", filename, i + 1, e);
+                    std::process::exit(1);
+                }
+            }
         }
     }
 
-    let mut safe_levels: i32 = 0;
+    let safe_levels = data
+        .iter()
+        .filter(|report| check_level(report, 0, &rules))
+        .count();
+    println!("Safe levels: {}", safe_levels);
+
+    let safe_levels = data
+        .iter()
+        .filter(|report| check_level(report, 1, &rules))
+        .count();
+    println!("Safe levels: {}", safe_levels);
+
+    // Optional tolerance for more than one bad level
+    if let Some(k) = tolerance {
+        let safe_levels = data
+            .iter()
+            .filter(|report| check_level(report, k, &rules))
+            .count();
+        println!("Safe levels with {} removals: {}", k, safe_levels);
+
+        let most = data
+            .iter()
+            .filter_map(|report| fewest_removals(report, &rules))
+            .max()
+            .unwrap_or(0);
+        println!("Most removals needed by a report: {}", most);
+    }
+
+    if table {
+        print_table(&data, &rules);
+    }
+    if csv {
+        print_csv(&data, &rules);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    for report in data.iter() {
-        if check_level(report) {
-            safe_levels += 1;
+    // Straight from the rules, every pair of neighbours checked
+    fn safe(levels: &[i32], rules: &Rules) -> bool {
+        let ok = |sign: i32| {
+            levels.windows(2).all(|pair| {
+                let step = (pair[1] - pair[0]) * sign;
+                (step == 0 && rules.allow_plateaus)
+                    || (step >= rules.min_step && step <= rules.max_step)
+            })
+        };
+        match rules.direction {
+            Direction::Increasing => ok(1),
+            Direction::Decreasing => ok(-1),
+            Direction::Either => ok(1) || ok(-1),
         }
     }
 
-    println!("Safe levels: {}", safe_levels);
+    // Tries every set of levels to remove
+    fn brute_force(levels: &[i32], k: usize, rules: &Rules) -> Option<usize> {
+        if levels.len() < 2 {
+            return None;
+        }
+        (0u32..1 << levels.len())
+            .filter(|removed| removed.count_ones() as usize <= k)
+            .filter(|removed| {
+                let kept: Vec<i32> = (0..levels.len())
+                    .filter(|i| removed & (1 << i) == 0)
+                    .map(|i| levels[i])
+                    .collect();
+                safe(&kept, rules)
+            })
+            .map(|removed| removed.count_ones() as usize)
+            .min()
+    }
+
+    #[test]
+    fn min_removals_matches_brute_force() {
+        let all_rules = [
+            Rules::default(),
+            Rules {
+                allow_plateaus: true,
+                ..Rules::default()
+            },
+            Rules {
+                min_step: 2,
+                max_step: 4,
+                direction: Direction::Increasing,
+                ..Rules::default()
+            },
+            Rules {
+                direction: Direction::Decreasing,
+                ..Rules::default()
+            },
+        ];
+        let mut seed: u64 = 0x2024_1202;
+        for _ in 0..2000 {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            let len = (seed % 9) as usize;
+            // Small values so steps are often in range and plateaus happen
+            let levels: Vec<i32> = (0..len)
+                .map(|i| ((seed >> (3 * i + 8)) % 7) as i32)
+                .collect();
+            for rules in &all_rules {
+                for k in 0..=3 {
+                    assert_eq!(
+                        min_removals(&levels, k, rules),
+                        brute_force(&levels, k, rules),
+                        "{:?} k={} {:?}",
+                        levels,
+                        k,
+                        rules
+                    );
+                }
+                assert_eq!(
+                    fewest_removals(&levels, rules),
+                    brute_force(&levels, levels.len(), rules)
+                );
+            }
+        }
+    }
 
-    safe_levels = 0;
+    #[test]
+    fn min_removals_on_the_example() {
+        let rules = Rules::default();
+        let reports = [
+            vec![7, 6, 4, 2, 1],
+            vec![1, 2, 7, 8, 9],
+            vec![9, 7, 6, 2, 1],
+            vec![1, 3, 2, 4, 5],
+            vec![8, 6, 4, 4, 1],
+            vec![1, 3, 6, 7, 9],
+        ];
+        let fewest: Vec<Option<usize>> =
+            reports.iter().map(|r| fewest_removals(r, &rules)).collect();
+        assert_eq!(
+            fewest,
+            vec![Some(0), Some(2), Some(2), Some(1), Some(1), Some(0)]
+        );
+        assert_eq!(min_removals(&reports[1], 1, &rules), None);
+    }
 
-    for report in data.iter() {
-        let mut safe = 0;
+    #[test]
+    fn classifies_the_example() {
+        let rules = Rules::default();
+        let verdicts: Vec<Verdict> = [
+            [7, 6, 4, 2, 1],
+            [1, 2, 7, 8, 9],
+            [9, 7, 6, 2, 1],
+            [1, 3, 2, 4, 5],
+            [8, 6, 4, 4, 1],
+            [1, 3, 6, 7, 9],
+        ]
+        .iter()
+        .map(|report| classify(report, &rules))
+        .collect();
+        assert_eq!(
+            verdicts,
+            vec![
+                Verdict::SafeDecreasing,
+                Verdict::Unsafe {
+                    index: 1,
+                    reason: Reason::StepTooLarge
+                },
+                Verdict::Unsafe {
+                    index: 2,
+                    reason: Reason::StepTooLarge
+                },
+                Verdict::SafeAfterRemoving(1),
+                Verdict::SafeAfterRemoving(2),
+                Verdict::SafeIncreasing,
+            ]
+        );
+        assert_eq!(verdicts[1].to_string(), "unsafe at 1-2: step above maximum");
+        assert_eq!(verdicts[3].to_string(), "safe after removing 1");
+    }
 
-        for i in 0..report.len() {
-            let mut cut = report.clone();
-            cut.remove(i);
-            if check_level(&cut) {
-                safe = 1;
-                break;
+    #[test]
+    fn classifies_every_reason() {
+        let rules = Rules::default();
+        assert_eq!(
+            classify(&[1, 2, 1, 2, 1], &rules),
+            Verdict::Unsafe {
+                index: 1,
+                reason: Reason::DirectionFlip
+            }
+        );
+        assert_eq!(
+            classify(&[4, 4, 4, 4], &rules),
+            Verdict::Unsafe {
+                index: 0,
+                reason: Reason::ZeroStep
             }
+        );
+        let wide = Rules {
+            min_step: 2,
+            ..rules
+        };
+        assert_eq!(
+            classify(&[1, 2, 3, 4], &wide),
+            Verdict::Unsafe {
+                index: 0,
+                reason: Reason::StepTooSmall
+            }
+        );
+        let flat = Rules {
+            allow_plateaus: true,
+            ..rules
+        };
+        assert_eq!(classify(&[5, 5, 5], &flat), Verdict::SafeFlat);
+        assert_eq!(classify(&[5, 5, 6, 6], &flat), Verdict::SafeIncreasing);
+        // Nothing to be flat about without a pair
+        for short in [&[][..], &[5]] {
+            assert_eq!(
+                classify(short, &flat),
+                Verdict::Unsafe {
+                    index: 0,
+                    reason: Reason::TooShort
+                }
+            );
+            assert!(!check_level(short, 1, &flat));
         }
-        safe_levels += safe;
     }
-    println!("Safe levels: {}", safe_levels);
+
+    #[test]
+    fn classify_agrees_with_one_removal() {
+        let rules = Rules::default();
+        for report in [
+            [1, 2, 1, 2, 1],
+            [9, 1, 2, 3, 4],
+            [1, 2, 3, 9, 4],
+            [3, 2, 1, 0, 9],
+        ] {
+            let safe = !matches!(classify(&report, &rules), Verdict::Unsafe { .. });
+            assert_eq!(safe, check_level(&report, 1, &rules), "{:?}", report);
+        }
+    }
+
+    // Writes a rules file of its own for each test, loads it and cleans up
+    fn load(name: &str, text: &str) -> (Rules, Result<(), String>, String) {
+        let path = std::env::temp_dir().join(format!("day2-{}-{}.rules", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        let filename = path.to_string_lossy().to_string();
+        let mut rules = Rules::default();
+        let loaded = rules.load(&filename);
+        std::fs::remove_file(&path).unwrap();
+        (rules, loaded, filename)
+    }
+
+    #[test]
+    fn sets_rules_by_name() {
+        let mut rules = Rules::default();
+        rules.set("min_step", "2").unwrap();
+        rules.set("max_step", "5").unwrap();
+        rules.set("allow_plateaus", "true").unwrap();
+        rules.set("direction", "decreasing").unwrap();
+        assert_eq!(
+            rules,
+            Rules {
+                min_step: 2,
+                max_step: 5,
+                allow_plateaus: true,
+                direction: Direction::Decreasing
+            }
+        );
+
+        assert_eq!(
+            rules.set("max_step", "x"),
+            Err("max_step: invalid number \"x\": invalid digit found in string".to_string())
+        );
+        assert_eq!(
+            rules.set("allow_plateaus", "yes"),
+            Err("allow_plateaus: expected true or false, got \"yes\"".to_string())
+        );
+        assert_eq!(
+            rules.set("direction", "up"),
+            Err("direction: expected increasing, decreasing or either, got \"up\"".to_string())
+        );
+        assert_eq!(
+            rules.set("steps", "3"),
+            Err("unknown rule \"steps\"".to_string())
+        );
+        // Failed settings leave the rules as they were
+        assert_eq!(rules.max_step, 5);
+
+        rules.set("min_step", "6").unwrap();
+        assert!(rules.validate().is_err());
+    }
+
+    #[test]
+    fn loads_a_rules_file() {
+        let (rules, loaded, _) = load(
+            "valid",
+            "# Wider steps\n\nmin_step = 2\n  max_step=6   # inclusive\ndirection = increasing\n",
+        );
+        assert_eq!(loaded, Ok(()));
+        assert_eq!(
+            rules,
+            Rules {
+                min_step: 2,
+                max_step: 6,
+                direction: Direction::Increasing,
+                ..Rules::default()
+            }
+        );
+    }
+
+    #[test]
+    fn rules_file_errors_give_the_line() {
+        let (_, loaded, filename) = load("missing", "min_step = 2\n# fine\nmax_step 4\n");
+        assert_eq!(loaded, Err(format!("{}:3: expected key = value", filename)));
+
+        let (_, loaded, filename) = load("number", "\nmax_step = four\n");
+        assert_eq!(
+            loaded,
+            Err(format!(
+                "{}:2: max_step: invalid number \"four\": invalid digit found in string",
+                filename
+            ))
+        );
+
+        let (_, loaded, filename) = load("unknown", "steps = 3");
+        assert_eq!(
+            loaded,
+            Err(format!("{}:1: unknown rule \"steps\"", filename))
+        );
+
+        let mut rules = Rules::default();
+        assert!(rules.load("/nonexistent/day2.rules").is_err());
+    }
 }
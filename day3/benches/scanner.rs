@@ -0,0 +1,72 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use day3::interpreter::Interpreter;
+use regex::Regex;
+
+// Corrupted memory with real and broken instructions mixed in with noise
+fn generate(len: usize) -> Vec<u8> {
+    let mut seed: u64 = 0x2024_1203;
+    let mut next = |n: u64| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed % n
+    };
+    let mut input = String::with_capacity(len + 16);
+    while input.len() < len {
+        let (a, b) = (next(1000), next(1000));
+        match next(8) {
+            0 | 1 => input.push_str(&format!("mul({},{})", a, b)),
+            2 => input.push_str("don't()"),
+            3 => input.push_str("do()"),
+            4 => input.push_str(&format!("mul[{},{}]", a, b)),
+            5 => input.push_str(&format!("mul({},{}!", a, b)),
+            _ => input.push_str("#why() &from%"),
+        }
+    }
+    input.into_bytes()
+}
+
+// The regex the scanner replaced, both parts from one pass over the captures
+fn with_regex(re: &Regex, input: &str) -> (i64, i64) {
+    let (mut p1, mut p2, mut enabled) = (0, 0, true);
+    for caps in re.captures_iter(input) {
+        match &caps["op"] {
+            "do" => enabled = true,
+            "don't" => enabled = false,
+            _ => {
+                let product = caps["a"].parse::<i64>().unwrap() * caps["b"].parse::<i64>().unwrap();
+                p1 += product;
+                if enabled {
+                    p2 += product;
+                }
+            }
+        }
+    }
+    (p1, p2)
+}
+
+fn scanner(c: &mut Criterion) {
+    let re = Regex::new(r"(?<op>mul|do|don't)\((?:(?<a>\d{1,3}),(?<b>\d{1,3}))?\)").unwrap();
+    let interpreter = Interpreter::part2();
+    let mut group = c.benchmark_group("scanner");
+    for len in [100_000, 1_000_000, 10_000_000] {
+        let input = generate(len);
+        let text = std::str::from_utf8(&input).unwrap();
+        let (p1, p2) = interpreter.run_both(&input[..]).unwrap();
+        assert_eq!(with_regex(&re, text), (p1.acc, p2.acc));
+
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("state machine", len),
+            &input,
+            |b, input| b.iter(|| interpreter.run_both(&input[..]).unwrap()),
+        );
+        group.bench_with_input(BenchmarkId::new("regex", len), text, |b, text| {
+            b.iter(|| with_regex(&re, text))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, scanner);
+criterion_main!(benches);
@@ -0,0 +1,204 @@
+use crate::interpreter::{CallError, Interpreter};
+use crate::scanner::MAX_DIGITS;
+use std::fmt;
+
+// Why something that looks like an instruction was not recognised
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Reason {
+    Whitespace,
+    TooManyDigits,
+    // Anything other than a digit, ',' or ')' inside the argument list
+    WrongSeparator(char),
+    // Nothing between two separators
+    MissingArgument,
+    // Input ended inside the argument list
+    Unterminated,
+    Call(CallError),
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Whitespace => write!(f, "whitespace"),
+            Reason::TooManyDigits => write!(f, "number longer than {} digits", MAX_DIGITS),
+            Reason::WrongSeparator(c) => write!(f, "unexpected {:?}", c),
+            Reason::MissingArgument => write!(f, "missing argument"),
+            Reason::Unterminated => write!(f, "no closing ')'"),
+            Reason::Call(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// A rejected fragment, start and end are byte offsets like in Call
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NearMiss {
+    pub start: usize,
+    pub end: usize,
+    pub reason: Reason,
+}
+
+// Every known name followed by '(' that the lexer would not accept
+// Names with nothing that looks like an argument list after them are not reported
+pub fn near_misses(input: &[u8], interpreter: &Interpreter) -> Vec<NearMiss> {
+    let names: Vec<&str> = interpreter
+        .instructions()
+        .iter()
+        .map(|i| i.signature.name.as_str())
+        .filter(|name| !name.is_empty())
+        .collect();
+
+    let mut misses = Vec::new();
+    for end in 1..=input.len() {
+        // The longest name ending here, the same choice the lexer makes
+        let Some(name) = names
+            .iter()
+            .filter(|name| input[..end].ends_with(name.as_bytes()))
+            .max_by_key(|name| name.len())
+        else {
+            continue;
+        };
+        let start = end - name.len();
+        if let Some((end, reason)) = inspect(input, end, name, interpreter) {
+            misses.push(NearMiss { start, end, reason });
+        }
+    }
+    misses
+}
+
+// Reads the argument list after a name starting at i
+// Returns where the fragment ends and its first problem, None if there is no
+// argument list or nothing is wrong with it
+fn inspect(
+    input: &[u8],
+    mut i: usize,
+    name: &str,
+    interpreter: &Interpreter,
+) -> Option<(usize, Reason)> {
+    let mut reason = None;
+    while input.get(i)?.is_ascii_whitespace() {
+        reason.get_or_insert(Reason::Whitespace);
+        i += 1;
+    }
+    if input[i] != b'(' {
+        return None;
+    }
+    i += 1;
+
+    let mut args = 0;
+    // Digits of the current argument, as usize since garbage can have any number of them
+    let mut digits: usize = 0;
+    let mut comma = false;
+    loop {
+        let Some(&byte) = input.get(i) else {
+            reason.get_or_insert(Reason::Unterminated);
+            break;
+        };
+        i += 1;
+        match byte {
+            b'0'..=b'9' => {
+                if digits == 0 {
+                    args += 1;
+                }
+                digits += 1;
+                if digits > MAX_DIGITS as usize {
+                    reason.get_or_insert(Reason::TooManyDigits);
+                }
+            }
+            b',' => {
+                if digits == 0 {
+                    reason.get_or_insert(Reason::MissingArgument);
+                }
+                digits = 0;
+                comma = true;
+            }
+            b')' => {
+                if comma && digits == 0 {
+                    reason.get_or_insert(Reason::MissingArgument);
+                }
+                break;
+            }
+            _ if byte.is_ascii_whitespace() => {
+                reason.get_or_insert(Reason::Whitespace);
+            }
+            _ => {
+                reason.get_or_insert(Reason::WrongSeparator(byte as char));
+                break;
+            }
+        }
+    }
+    let reason = match reason {
+        Some(reason) => reason,
+        None => Reason::Call(interpreter.check(name, args).err()?),
+    };
+    Some((i, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn misses(input: &str) -> Vec<(&str, Reason)> {
+        near_misses(input.as_bytes(), &Interpreter::part2())
+            .into_iter()
+            .map(|miss| (&input[miss.start..miss.end], miss.reason))
+            .collect()
+    }
+
+    #[test]
+    fn reports_the_request_examples() {
+        assert_eq!(
+            misses("xmul(4*3)"),
+            vec![("mul(4*", Reason::WrongSeparator('*'))]
+        );
+        assert_eq!(
+            misses("mul ( 2 , 4 )!"),
+            vec![("mul ( 2 , 4 )", Reason::Whitespace)]
+        );
+        assert_eq!(
+            misses("mul(1234,5)"),
+            vec![("mul(1234,5)", Reason::TooManyDigits)]
+        );
+    }
+
+    #[test]
+    fn reports_every_reason() {
+        assert_eq!(
+            misses("mul(2,)mul(,4)"),
+            vec![
+                ("mul(2,)", Reason::MissingArgument),
+                ("mul(,4)", Reason::MissingArgument)
+            ]
+        );
+        assert_eq!(misses("mul(2,4"), vec![("mul(2,4", Reason::Unterminated)]);
+        // More digits than a u8 can count
+        let long = format!("mul({},2)", "1".repeat(300));
+        assert_eq!(misses(&long), vec![(&long[..], Reason::TooManyDigits)]);
+        assert_eq!(
+            misses("mul(2)do(1)"),
+            vec![
+                (
+                    "mul(2)",
+                    Reason::Call(CallError::Arity {
+                        name: "mul".to_string(),
+                        expected: 2..=2,
+                        found: 1
+                    })
+                ),
+                (
+                    "do(1)",
+                    Reason::Call(CallError::Arity {
+                        name: "do".to_string(),
+                        expected: 0..=0,
+                        found: 1
+                    })
+                )
+            ]
+        );
+    }
+
+    #[test]
+    fn valid_instructions_and_plain_names_are_not_reported() {
+        assert_eq!(misses("mul(2,4)don't()do()"), vec![]);
+        assert_eq!(misses("mul[3,7] do mul"), vec![]);
+    }
+}
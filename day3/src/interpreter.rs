@@ -0,0 +1,311 @@
+use crate::scanner::{Call, Lexer, Scanner, Signature};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::ops::RangeInclusive;
+
+// State the instructions work on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Machine {
+    pub enabled: bool,
+    pub acc: i64,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Machine {
+            enabled: true,
+            acc: 0,
+        }
+    }
+}
+
+// What an instruction does with its arguments, the count is checked by the lexer
+pub type Effect = fn(&mut Machine, &[i32]);
+
+#[derive(Clone, Debug)]
+pub struct Instruction {
+    pub signature: Signature,
+    // Gated instructions are skipped while the machine is disabled
+    pub gated: bool,
+    pub effect: Effect,
+}
+
+impl Instruction {
+    pub fn new(name: &str, arity: RangeInclusive<usize>, effect: Effect) -> Self {
+        Instruction {
+            signature: Signature {
+                name: name.to_string(),
+                arity,
+            },
+            gated: true,
+            effect,
+        }
+    }
+
+    // Runs even while the machine is disabled, needed for anything that enables it again
+    pub fn ungated(mut self) -> Self {
+        self.gated = false;
+        self
+    }
+
+    pub fn mul() -> Self {
+        Instruction::new("mul", 2..=2, |m, args| {
+            m.acc += args[0] as i64 * args[1] as i64
+        })
+    }
+
+    pub fn enable() -> Self {
+        Instruction::new("do", 0..=0, |m, _| m.enabled = true).ungated()
+    }
+
+    pub fn disable() -> Self {
+        Instruction::new("don't", 0..=0, |m, _| m.enabled = false).ungated()
+    }
+}
+
+// One recognised instruction and what it did
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Step {
+    pub call: Call,
+    // False when a gated instruction was skipped
+    pub executed: bool,
+    // Whether the machine was enabled before the instruction
+    pub enabled: bool,
+    // Change to the accumulator
+    pub contribution: i64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CallError {
+    Unknown(String),
+    Arity {
+        name: String,
+        expected: RangeInclusive<usize>,
+        found: usize,
+    },
+}
+
+impl fmt::Display for CallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallError::Unknown(name) => write!(f, "unknown instruction {:?}", name),
+            CallError::Arity {
+                name,
+                expected,
+                found,
+            } if expected.start() == expected.end() => write!(
+                f,
+                "{} takes {} arguments, found {}",
+                name,
+                expected.start(),
+                found
+            ),
+            CallError::Arity {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} takes {} to {} arguments, found {}",
+                name,
+                expected.start(),
+                expected.end(),
+                found
+            ),
+        }
+    }
+}
+
+impl Error for CallError {}
+
+// Table of known instructions, anything not in it is corrupted memory
+#[derive(Clone, Debug, Default)]
+pub struct Interpreter {
+    instructions: Vec<Instruction>,
+}
+
+impl Interpreter {
+    // Part 1, only mul
+    pub fn part1() -> Self {
+        let mut interpreter = Interpreter::default();
+        interpreter.register(Instruction::mul());
+        interpreter
+    }
+
+    // Part 2, mul switched on and off by do() and don't()
+    pub fn part2() -> Self {
+        let mut interpreter = Interpreter::part1();
+        interpreter.register(Instruction::enable());
+        interpreter.register(Instruction::disable());
+        interpreter
+    }
+
+    // An instruction with the same name as an earlier one replaces it
+    pub fn register(&mut self, instruction: Instruction) {
+        let name = &instruction.signature.name;
+        match self
+            .instructions
+            .iter_mut()
+            .find(|i| &i.signature.name == name)
+        {
+            Some(existing) => *existing = instruction,
+            None => self.instructions.push(instruction),
+        }
+    }
+
+    // Opcode for name called with args arguments
+    pub fn check(&self, name: &str, args: usize) -> Result<usize, CallError> {
+        let opcode = self
+            .instructions
+            .iter()
+            .position(|i| i.signature.name == name)
+            .ok_or_else(|| CallError::Unknown(name.to_string()))?;
+        let arity = &self.instructions[opcode].signature.arity;
+        if !arity.contains(&args) {
+            return Err(CallError::Arity {
+                name: name.to_string(),
+                expected: arity.clone(),
+                found: args,
+            });
+        }
+        Ok(opcode)
+    }
+
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    pub fn lexer(&self) -> Lexer {
+        Lexer::new(
+            self.instructions
+                .iter()
+                .map(|i| i.signature.clone())
+                .collect(),
+        )
+    }
+
+    // Returns false if the call was skipped because the machine is disabled
+    pub fn execute(&self, machine: &mut Machine, call: &Call) -> bool {
+        let instruction = &self.instructions[call.opcode];
+        if instruction.gated && !machine.enabled {
+            return false;
+        }
+        (instruction.effect)(machine, &call.args);
+        true
+    }
+
+    // Same as execute, but gated instructions run even while the machine is disabled
+    pub fn execute_ungated(&self, machine: &mut Machine, call: &Call) {
+        (self.instructions[call.opcode].effect)(machine, &call.args);
+    }
+
+    pub fn run<R: BufRead>(&self, reader: R) -> io::Result<Machine> {
+        let mut machine = Machine::default();
+        for call in Scanner::new(reader, self.lexer()) {
+            self.execute(&mut machine, &call?);
+        }
+        Ok(machine)
+    }
+
+    // Runs the input once with gating and once without in the same pass, so part 1
+    // and part 2 come from one read of the input. The first machine ignores gating
+    pub fn run_both<R: BufRead>(&self, reader: R) -> io::Result<(Machine, Machine)> {
+        let mut ungated = Machine::default();
+        let mut gated = Machine::default();
+        for call in Scanner::new(reader, self.lexer()) {
+            let call = call?;
+            self.execute_ungated(&mut ungated, &call);
+            self.execute(&mut gated, &call);
+        }
+        Ok((ungated, gated))
+    }
+
+    // Same as run, but keeps every step for inspection
+    pub fn trace<R: BufRead>(&self, reader: R) -> io::Result<(Machine, Vec<Step>)> {
+        let mut machine = Machine::default();
+        let mut steps = Vec::new();
+        for call in Scanner::new(reader, self.lexer()) {
+            let call = call?;
+            let (enabled, acc) = (machine.enabled, machine.acc);
+            let executed = self.execute(&mut machine, &call);
+            steps.push(Step {
+                call,
+                executed,
+                enabled,
+                contribution: machine.acc - acc,
+            });
+        }
+        Ok((machine, steps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &[u8] =
+        b"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    #[test]
+    fn one_pass_gives_both_parts() {
+        let (p1, p2) = Interpreter::part2().run_both(EXAMPLE).unwrap();
+        assert_eq!((p1.acc, p2.acc), (161, 48));
+        assert_eq!(p1.acc, Interpreter::part1().run(EXAMPLE).unwrap().acc);
+        assert_eq!(p2, Interpreter::part2().run(EXAMPLE).unwrap());
+    }
+
+    #[test]
+    fn check_names_and_argument_counts() {
+        let interpreter = Interpreter::part2();
+        assert_eq!(interpreter.check("mul", 2), Ok(0));
+        assert_eq!(interpreter.check("don't", 0), Ok(2));
+        assert_eq!(
+            interpreter.check("add", 2),
+            Err(CallError::Unknown("add".to_string()))
+        );
+        let e = interpreter.check("mul", 3).unwrap_err();
+        assert_eq!(
+            e,
+            CallError::Arity {
+                name: "mul".to_string(),
+                expected: 2..=2,
+                found: 3
+            }
+        );
+        assert_eq!(e.to_string(), "mul takes 2 arguments, found 3");
+    }
+
+    #[test]
+    fn registered_instructions_run() {
+        let mut interpreter = Interpreter::part2();
+        interpreter.register(Instruction::new("add", 1..=3, |m, args| {
+            m.acc += args.iter().map(|&a| a as i64).sum::<i64>()
+        }));
+        // Gated and ungated versions of the same effect
+        let negate: Effect = |m, _| m.acc = -m.acc;
+        interpreter.register(Instruction::new("neg", 0..=0, negate));
+        interpreter.register(Instruction::new("force_neg", 0..=0, negate).ungated());
+        assert_eq!(interpreter.check("add", 3), Ok(3));
+
+        // add() and add(1,2,3,4) have the wrong argument count and are garbage.
+        // Only force_neg runs while disabled, so this is -(1 + 6) + 4
+        let input = b"add(1)add(1,2,3)add()add(1,2,3,4)don't()add(5)neg()force_neg()do()add(2,2)";
+        let machine = interpreter.run(&input[..]).unwrap();
+        assert_eq!(machine.acc, -3);
+        assert!(machine.enabled);
+    }
+
+    #[test]
+    fn register_replaces_an_instruction_with_the_same_name() {
+        let mut interpreter = Interpreter::part2();
+        interpreter.register(Instruction::new("mul", 3..=3, |m, args| {
+            m.acc += args[0] as i64 * args[1] as i64 * args[2] as i64
+        }));
+        assert_eq!(interpreter.instructions().len(), 3);
+        assert_eq!(interpreter.check("mul", 3), Ok(0));
+        assert!(interpreter.check("mul", 2).is_err());
+        let machine = interpreter.run(&b"mul(2,3)mul(2,3,4)"[..]).unwrap();
+        assert_eq!(machine.acc, 24);
+    }
+}
@@ -0,0 +1,3 @@
+pub mod diagnostics;
+pub mod interpreter;
+pub mod scanner;
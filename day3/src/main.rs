@@ -1,76 +1,140 @@
-use regex::Regex;
+use day3::diagnostics::{near_misses, NearMiss};
+use day3::interpreter::{Interpreter, Step};
+use day3::scanner::CHUNK;
 use std::env;
-use std::num::ParseIntError;
+use std::fs::File;
+use std::io::BufReader;
 
-enum Operation {
-    Mul(i32, i32),
-    Do,
-    Dont,
+// ANSI styles for the highlighted input. Garbage is dimmed in the colour of its
+// region, instructions are bold in a colour of their own
+const ENABLED: &str = "\x1b[2;32m";
+const DISABLED: &str = "\x1b[2;31m";
+const COUNTED: &str = "\x1b[1;32m";
+const SKIPPED: &str = "\x1b[1;9;31m";
+const SWITCH: &str = "\x1b[1;33m";
+const RESET: &str = "\x1b[0m";
+
+// Both parts in one pass, part 1 is part 2 with do() and don't() ignored
+fn run(filename: &str) -> (i64, i64) {
+    let file = File::open(filename).expect("Unable to read file");
+    let (p1, p2) = Interpreter::part2()
+        .run_both(BufReader::with_capacity(CHUNK, file))
+        .expect("Failed to read operations");
+    (p1.acc, p2.acc)
 }
 
-impl Operation {
-    fn from_caps(caps: &regex::Captures) -> Result<Self, ParseIntError> {
-        match caps.name("op").unwrap().as_str() {
-            "mul" => {
-                let (val1, val2) = caps["vals"]
-                    .split_once(',')
-                    .expect("Expected two values separated by a comma");
-                let val1: i32 = val1.parse()?;
-                let val2: i32 = val2.parse()?;
-                Ok(Operation::Mul(val1, val2))
-            }
-            "do" => Ok(Operation::Do),
-            "don't" => Ok(Operation::Dont),
-            _ => panic!("Unknown operation"),
-        }
+// One line per recognised instruction with where it is and what it added
+fn print_steps(input: &[u8], steps: &[Step]) {
+    let text = |step: &Step| String::from_utf8_lossy(&input[step.call.start..step.call.end]);
+    let width = steps
+        .iter()
+        .map(|step| text(step).len())
+        .max()
+        .unwrap_or(0)
+        .max("instruction".len());
+    println!(
+        "{:>8}  {:<width$}  {:<8}  contribution",
+        "offset", "instruction", "state"
+    );
+    for step in steps {
+        let state = if step.enabled { "enabled" } else { "disabled" };
+        let contribution = if step.executed {
+            step.contribution.to_string()
+        } else {
+            "skipped".to_string()
+        };
+        println!(
+            "{:>8}  {:<width$}  {:<8}  {}",
+            step.call.start,
+            text(step),
+            state,
+            contribution
+        );
+    }
+}
+
+// The input with the garbage between instructions in dim green where the
+// machine is enabled and dim red where it is disabled. Gated instructions are
+// bold green when counted and struck out in red when skipped, do() and don't()
+// are yellow
+fn print_highlighted(input: &[u8], steps: &[Step], enabled: bool, interpreter: &Interpreter) {
+    let region = |enabled: bool| if enabled { ENABLED } else { DISABLED };
+    let mut out = String::new();
+    let mut at = 0;
+    for step in steps {
+        let (start, end) = (step.call.start, step.call.end);
+        // step.enabled is the state left by the instruction before
+        out.push_str(region(step.enabled));
+        out.push_str(&String::from_utf8_lossy(&input[at..start]));
+        out.push_str(RESET);
+        let gated = interpreter.instructions()[step.call.opcode].gated;
+        out.push_str(match (gated, step.executed) {
+            (false, _) => SWITCH,
+            (true, true) => COUNTED,
+            (true, false) => SKIPPED,
+        });
+        out.push_str(&String::from_utf8_lossy(&input[start..end]));
+        out.push_str(RESET);
+        at = end;
     }
+    out.push_str(region(enabled));
+    out.push_str(String::from_utf8_lossy(&input[at..]).trim_end_matches('\n'));
+    out.push_str(RESET);
+    println!("{}", out);
+    println!(
+        "{}garbage while enabled{} {}garbage while disabled{} {}counted{} {}skipped{} {}switch{}",
+        ENABLED, RESET, DISABLED, RESET, COUNTED, RESET, SKIPPED, RESET, SWITCH, RESET
+    );
+}
+
+fn print_near_misses(input: &[u8], misses: &[NearMiss]) {
+    println!("{:>8}  fragment  reason", "offset");
+    for miss in misses {
+        let fragment = String::from_utf8_lossy(&input[miss.start..miss.end]);
+        println!("{:>8}  {:?}  {}", miss.start, fragment, miss.reason);
+    }
+}
+
+fn usage(program: &str) -> ! {
+    eprintln!("Usage: {} <filename> [--trace] [--diagnose]", program);
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-
     if args.len() < 2 {
-        eprintln!("Usage: {} <filename>", args[0]);
-        std::process::exit(1);
+        usage(&args[0]);
     }
     let filename = &args[1];
 
-    let data = std::fs::read_to_string(filename).expect("Unable to read file");
-
-    let re = Regex::new(r"(?<op>mul|do|don\'t)(\((?<vals>(\d{1,3})\,(\d{1,3}))?\))").unwrap();
-
-    let ops: Vec<Operation> = re
-        .captures_iter(&data)
-        .map(|caps| Operation::from_caps(&caps))
-        .collect::<Result<_, _>>()
-        .expect("Failed to parse operations");
-
-    // P1
-    let sum: i32 = ops
-        .iter()
-        .filter_map(|op| {
-            if let Operation::Mul(a, b) = op {
-                Some(a * b)
-            } else {
-                None
-            }
-        })
-        .sum();
-
-    println!("P1: {}", sum);
-
-    // P2
-    let mut enabled = true;
-    let mut sum2 = 0;
-
-    for op in &ops {
-        match op {
-            Operation::Mul(a, b) if enabled => sum2 += a * b,
-            Operation::Do => enabled = true,
-            Operation::Dont => enabled = false,
-            _ => {}
+    let mut trace = false;
+    let mut diagnose = false;
+    for option in &args[2..] {
+        match option.as_str() {
+            "--trace" => trace = true,
+            "--diagnose" => diagnose = true,
+            _ => usage(&args[0]),
         }
     }
 
-    println!("P2: {}", sum2);
+    let (p1, p2) = run(filename);
+    println!("P1: {}", p1);
+    println!("P2: {}", p2);
+    if !trace && !diagnose {
+        return;
+    }
+
+    // Both of these need the whole input in memory
+    let input = std::fs::read(filename).expect("Unable to read file");
+    let interpreter = Interpreter::part2();
+    if trace {
+        let (machine, steps) = interpreter
+            .trace(&input[..])
+            .expect("Failed to read operations");
+        print_steps(&input, &steps);
+        print_highlighted(&input, &steps, machine.enabled, &interpreter);
+    }
+    if diagnose {
+        print_near_misses(&input, &near_misses(&input, &interpreter));
+    }
 }
@@ -0,0 +1,272 @@
+use std::io::{self, BufRead};
+use std::ops::RangeInclusive;
+
+// Bytes read from the input at a time
+pub const CHUNK: usize = 4096;
+
+// Arguments are 1-3 digit numbers
+pub const MAX_DIGITS: u8 = 3;
+
+// An instruction name and how many arguments it takes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    pub name: String,
+    pub arity: RangeInclusive<usize>,
+}
+
+// A recognised instruction, opcode is the index of its signature in the lexer
+// start and end are byte offsets into the input, end is one past the ')'
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Call {
+    pub opcode: usize,
+    pub args: Vec<i32>,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    // Looking for a name followed by '('
+    Name,
+    // Inside the argument list, digits counts the digits of the last argument
+    Args { opcode: usize, digits: u8 },
+}
+
+// Byte at a time recogniser for name(a,b,...) with the names it is given
+// Keeps only the current state, so instructions split across reads are no problem
+#[derive(Debug)]
+pub struct Lexer {
+    signatures: Vec<Signature>,
+    // The last bytes seen, as many as the longest name, to look up names at '('
+    recent: Vec<u8>,
+    longest: usize,
+    state: State,
+    args: Vec<i32>,
+    // Offset of the next byte and of the name being read
+    position: usize,
+    start: usize,
+}
+
+impl Lexer {
+    pub fn new(signatures: Vec<Signature>) -> Self {
+        let longest = signatures.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        Lexer {
+            signatures,
+            recent: Vec::with_capacity(longest),
+            longest,
+            state: State::Name,
+            args: Vec::new(),
+            position: 0,
+            start: 0,
+        }
+    }
+
+    pub fn signatures(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    pub fn feed(&mut self, byte: u8) -> Option<Call> {
+        let call = match self.state {
+            State::Name => {
+                if byte == b'(' {
+                    if let Some(opcode) = self.lookup() {
+                        self.args.clear();
+                        self.start = self.position - self.signatures[opcode].name.len();
+                        self.state = State::Args { opcode, digits: 0 };
+                    }
+                }
+                None
+            }
+            State::Args { opcode, digits } => match self.step(opcode, digits, byte) {
+                Some(call) => call,
+                // A broken argument list may still be followed by a name,
+                // so the byte is looked at again from the start
+                None => {
+                    self.state = State::Name;
+                    return self.feed(byte);
+                }
+            },
+        };
+        self.remember(byte);
+        self.position += 1;
+        call
+    }
+
+    // Goes over the bytes before the next '(' at once while no argument list is open,
+    // nothing can start before it. Returns how many bytes were taken
+    pub fn skip(&mut self, bytes: &[u8]) -> usize {
+        if self.state != State::Name {
+            return 0;
+        }
+        let n = bytes.iter().position(|&b| b == b'(').unwrap_or(bytes.len());
+        if n >= self.longest {
+            self.recent.clear();
+            self.recent.extend_from_slice(&bytes[n - self.longest..n]);
+        } else {
+            for &byte in &bytes[..n] {
+                self.remember(byte);
+            }
+        }
+        self.position += n;
+        n
+    }
+
+    // None when byte cannot continue the argument list
+    fn step(&mut self, opcode: usize, digits: u8, byte: u8) -> Option<Option<Call>> {
+        let arity = &self.signatures[opcode].arity;
+        match byte {
+            b'0'..=b'9' if digits < MAX_DIGITS => {
+                if digits == 0 {
+                    self.args.push(0);
+                }
+                let last = self.args.last_mut()?;
+                *last = *last * 10 + (byte - b'0') as i32;
+                self.state = State::Args {
+                    opcode,
+                    digits: digits + 1,
+                };
+                Some(None)
+            }
+            b',' if digits > 0 && self.args.len() < *arity.end() => {
+                self.state = State::Args { opcode, digits: 0 };
+                Some(None)
+            }
+            b')' if (digits > 0 || self.args.is_empty()) && arity.contains(&self.args.len()) => {
+                self.state = State::Name;
+                Some(Some(Call {
+                    opcode,
+                    args: std::mem::take(&mut self.args),
+                    start: self.start,
+                    end: self.position + 1,
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    // The longest name that ends right before the current byte
+    fn lookup(&self) -> Option<usize> {
+        self.signatures
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| !s.name.is_empty() && self.recent.ends_with(s.name.as_bytes()))
+            .max_by_key(|(_, s)| s.name.len())
+            .map(|(opcode, _)| opcode)
+    }
+
+    fn remember(&mut self, byte: u8) {
+        if self.longest == 0 {
+            return;
+        }
+        if self.recent.len() == self.longest {
+            self.recent.remove(0);
+        }
+        self.recent.push(byte);
+    }
+}
+
+// Pulls calls out of a reader without holding more than one chunk in memory
+pub struct Scanner<R> {
+    reader: R,
+    lexer: Lexer,
+}
+
+impl<R: BufRead> Scanner<R> {
+    pub fn new(reader: R, lexer: Lexer) -> Self {
+        Scanner { reader, lexer }
+    }
+}
+
+impl<R: BufRead> Iterator for Scanner<R> {
+    type Item = io::Result<Call>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let chunk = match self.reader.fill_buf() {
+                Ok(chunk) => chunk,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e)),
+            };
+            if chunk.is_empty() {
+                return None;
+            }
+            let mut i = 0;
+            while i < chunk.len() {
+                i += self.lexer.skip(&chunk[i..]);
+                if i == chunk.len() {
+                    break;
+                }
+                let call = self.lexer.feed(chunk[i]);
+                i += 1;
+                if let Some(call) = call {
+                    self.reader.consume(i);
+                    return Some(Ok(call));
+                }
+            }
+            let len = chunk.len();
+            self.reader.consume(len);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    const EXAMPLE: &[u8] =
+        b"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+    fn lexer() -> Lexer {
+        Lexer::new(vec![
+            Signature {
+                name: "mul".to_string(),
+                arity: 2..=2,
+            },
+            Signature {
+                name: "do".to_string(),
+                arity: 0..=0,
+            },
+            Signature {
+                name: "don't".to_string(),
+                arity: 0..=0,
+            },
+        ])
+    }
+
+    fn scan<R: BufRead>(reader: R) -> Vec<Call> {
+        Scanner::new(reader, lexer())
+            .collect::<io::Result<_>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn scans_the_example() {
+        let calls: Vec<(usize, Vec<i32>, usize)> = scan(EXAMPLE)
+            .into_iter()
+            .map(|call| (call.opcode, call.args, call.start))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                (0, vec![2, 4], 1),
+                (2, vec![], 20),
+                (0, vec![5, 5], 28),
+                (0, vec![11, 8], 48),
+                (1, vec![], 59),
+                (0, vec![8, 5], 64),
+            ]
+        );
+    }
+
+    #[test]
+    fn instructions_split_across_reads() {
+        // A capacity of 1 splits every instruction after each byte, the others
+        // split them in different places
+        let whole = scan(EXAMPLE);
+        for capacity in 1..=20 {
+            let chunked = scan(BufReader::with_capacity(capacity, EXAMPLE));
+            assert_eq!(chunked, whole, "capacity {}", capacity);
+        }
+    }
+}
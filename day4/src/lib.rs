@@ -0,0 +1,3 @@
+pub mod matrix;
+pub mod search;
+pub mod template;
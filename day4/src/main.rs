@@ -1,234 +1,121 @@
+use day4::matrix::Matrix;
+use day4::search::{direction_name, Engine, Match, Options, Palindromes};
+use day4::template::{Template, TemplateMatch};
 use std::env;
-use std::fmt;
-use std::fs::File;
-use std::io::{self, BufRead};
-use std::ops::{Div, Rem};
-use std::path::Path;
 
-#[derive(Debug, Default)]
-pub struct Matrix<T> {
-    cols: usize,
-    data: Vec<T>,
-}
-
-impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
-    pub fn new(cols: usize, data: Vec<T>) -> Self {
-        Self { cols, data }
-    }
-    // Returns T, pos, and direction from the origin
-    // i=x, k=y
-    pub fn neighbours(&self, pos: usize) -> Vec<Neighbour<T>> {
-        let ipos = pos as isize;
-        //println!("ipos: {}", ipos);
-        let (cur_x, cur_y) = self.icoords_from_pos(pos);
-        let icols = self.cols as isize;
-        let mut neighbours: Vec<Neighbour<T>> = Vec::new();
-        for i in -1..=1 {
-            for k in -1..=1 {
-                let new_row = cur_y + i;
-                let new_col = cur_x + k;
-                //println!(
-                //    "i: {}, k: {}, new_col: {}, new_row: {}",
-                //    i, k, new_col, new_row
-                //);
-                if !(i == 0 && k == 0)
-                    && new_row >= 0
-                    && new_row < self.data.len().div(self.cols) as isize
-                    && new_col >= 0
-                    && new_col < icols
-                {
-                    let new_pos = ipos + icols * i + k;
-                    let unew_pos = new_pos as usize;
-                    //println!(
-                    //    "i: {}, k: {}, new_pos: {}, new_col: {}, index: {} undex: {}",
-                    //    i, k, new_pos, new_row, new_pos, unew_pos
-                    //);
-                    match self.data.get(unew_pos) {
-                        Some(n) => {
-                            //println!("Creating neighbour, i: {}, k: {}", i, k);
-                            let neighbour = Neighbour {
-                                c: *n,
-                                pos: unew_pos,
-                                x_dir: k,
-                                y_dir: i,
-                            };
-                            neighbours.push(neighbour)
-                        }
-                        _ => {}
-                    }
-                }
-            }
-        }
-        neighbours
-    }
-
-    pub fn get(&self, x: isize, y: isize) -> Option<T> {
-        if x >= 0
-            && y >= 0
-            && x < self.cols.try_into().unwrap()
-            && y < self.data.len().div(self.cols).try_into().unwrap()
-        {
-            let pos = self.pos_from_coords(x.try_into().unwrap(), y.try_into().unwrap());
-            if pos >= self.data.len() {
-                return None;
-            }
-            return Some(self.data[pos]);
-        }
-        None
-    }
-
-    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
-        return &self.cols * y + x;
-    }
-
-    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
-        let x = pos.rem(&self.cols);
-        let y = pos.div(&self.cols);
-        return (x, y);
-    }
-
-    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
-        let x = pos.rem(&self.cols) as isize;
-        let y = pos.div(&self.cols) as isize;
-        return (x, y);
+// Part 2, two MAS crossing on the A, in any orientation
+const X_MAS: &str = "M.S/.A./M.S";
+
+// Each match on its own line, then the grid with only the matched letters
+fn show_words(puzzle: &Matrix<char>, words: &[&str], matches: &[Match], wrap: bool) {
+    for m in matches {
+        println!(
+            "{} at ({}, {}) going {}",
+            words[m.word],
+            m.x,
+            m.y,
+            direction_name(m.direction)
+        );
     }
+    let cells = matches
+        .iter()
+        .flat_map(|m| puzzle.word_cells(m, words[m.word].chars().count(), wrap));
+    println!("{}\n", puzzle.reveal(cells));
 }
 
-impl fmt::Display for Matrix<char> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 && i >= self.cols {
-                write!(f, "\n")?;
-            }
-            write!(f, "{} ", c)?;
-        }
-        Ok(())
+fn show_template(
+    puzzle: &Matrix<char>,
+    pattern: &str,
+    template: &Template,
+    matches: &[TemplateMatch],
+) {
+    for m in matches {
+        println!("{} at ({}, {}) {}", pattern, m.x, m.y, m.transform);
     }
+    let cells = matches.iter().flat_map(|m| template.cells(m));
+    println!("{}\n", puzzle.reveal(cells));
 }
 
-#[derive(Debug, Default)]
-pub struct Neighbour<T> {
-    c: T,
-    pos: usize,
-    x_dir: isize,
-    y_dir: isize,
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} <filename> [--word WORD]... [--no-overlaps] [--palindromes-twice] [--ignore-case] [--aho-corasick] [--wrap] [--template PATTERN]... [--orientations] [--show]",
+        program
+    );
+    std::process::exit(1);
 }
 
-impl Neighbour<char> {
-    pub fn solve(&self, data: &Matrix<char>) -> bool {
-        if self.c == 'M' {
-            match &self.next(&data) {
-                Some(a) if a.c == 'A' => {
-                    match a.next(&data) {
-                        Some(s) if s.c == 'S' => {
-                            return true;
-                        }
-                        _ => return false,
-                    };
-                }
-                _ => return false,
-            };
-        };
-        return false;
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        usage(&args[0]);
     }
+    let filename = &args[1];
 
-    fn next(&self, data: &Matrix<char>) -> Option<Self> {
-        let (cur_x, cur_y) = data.coords_from_pos(self.pos);
-        let next_x = cur_x as isize + self.x_dir as isize;
-        let next_y = cur_y as isize + self.y_dir as isize;
-
-        match data.get(next_x, next_y) {
-            Some(next_neighbour) => {
-                let next_pos =
-                    data.pos_from_coords(next_x.try_into().unwrap(), next_y.try_into().unwrap());
-                let next_neighbour = Neighbour {
-                    c: next_neighbour,
-                    pos: next_pos,
-                    x_dir: self.x_dir,
-                    y_dir: self.y_dir,
-                };
-                return Some(next_neighbour);
-            }
-            None => return None,
+    let mut words: Vec<&str> = Vec::new();
+    let mut options = Options::default();
+    let mut templates: Vec<&str> = Vec::new();
+    let mut orientations = false;
+    let mut show = false;
+    let mut flags = args[2..].iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--word" => words.push(flags.next().unwrap_or_else(|| usage(&args[0]))),
+            "--no-overlaps" => options.overlaps = false,
+            "--palindromes-twice" => options.palindromes = Palindromes::Twice,
+            "--ignore-case" => options.ignore_case = true,
+            "--aho-corasick" => options.engine = Engine::AhoCorasick,
+            "--wrap" => options.wrap = true,
+            "--template" => templates.push(flags.next().unwrap_or_else(|| usage(&args[0]))),
+            "--orientations" => orientations = true,
+            "--show" => show = true,
+            _ => usage(&args[0]),
         }
     }
-}
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-
-pub fn solve2(neighbours: Vec<Neighbour<char>>) -> bool {
-    if neighbours.len() < 8 {
-        return false;
+    let data = std::fs::read_to_string(filename).expect("Unable to read file");
+    let puzzle: Matrix<char> = match data.parse() {
+        Ok(puzzle) => puzzle,
+        Err(e) => {
+            eprintln!("{}: {}", filename, e);
+            std::process::exit(1);
+        }
     };
 
-    let first: Vec<char> = neighbours
-        .iter()
-        .filter(|n| n.x_dir == -1 && n.y_dir == -1 || n.x_dir == 1 && n.y_dir == 1)
-        .map(|x| x.c)
-        .collect();
-    let second: Vec<char> = neighbours
-        .iter()
-        .filter(|n| n.x_dir == -1 && n.y_dir == 1 || n.x_dir == 1 && n.y_dir == -1)
-        .map(|x| x.c)
-        .collect();
+    let xmas = puzzle.find_words(&["XMAS"], &Options::default());
 
-    let criteria: Vec<char> = vec!['M', 'S'];
+    let x_mas: Template = X_MAS.parse().expect("Invalid built in template");
+    let x_mas_matches = puzzle.match_template(&x_mas, true);
 
-    if criteria.iter().all(|item| first.contains(item))
-        && criteria.iter().all(|item| second.contains(item))
-    {
-        return true;
+    println!("P1: {}", xmas.len());
+    println!("P2: {}", x_mas_matches.len());
+    if show {
+        show_words(&puzzle, &["XMAS"], &xmas, false);
+        show_template(&puzzle, X_MAS, &x_mas, &x_mas_matches);
     }
 
-    false
-}
-
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let filename = &args[1];
-    let mut data = Vec::new();
-    let mut rows: usize = 0;
-
-    if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
-            for c in line.chars() {
-                data.push(c);
-            }
-            rows += 1;
+    if !words.is_empty() {
+        let matches = puzzle.find_words(&words, &options);
+        for (w, word) in words.iter().enumerate() {
+            let found = matches.iter().filter(|m| m.word == w).count();
+            println!("{}: {}", word, found);
         }
-    }
-
-    let cols = data.len().div(rows);
-    let puzzle: Matrix<char> = Matrix::new(cols, data);
-
-    let mut count: usize = 0;
-    for (i, point) in puzzle.data.iter().enumerate() {
-        if *point == 'X' {
-            for neighbour in puzzle.neighbours(i) {
-                if neighbour.solve(&puzzle) {
-                    count += 1;
-                }
-            }
+        if show {
+            show_words(&puzzle, &words, &matches, options.wrap);
         }
     }
 
-    let mut count2: usize = 0;
-
-    for (i, point) in puzzle.data.iter().enumerate() {
-        if *point == 'A' {
-            let neighbours = puzzle.neighbours(i);
-            if solve2(neighbours) {
-                count2 += 1;
+    for pattern in templates {
+        let template: Template = match pattern.parse() {
+            Ok(template) => template,
+            Err(e) => {
+                eprintln!("{}: {}", pattern, e);
+                std::process::exit(1);
             }
+        };
+        let matches = puzzle.match_template(&template, orientations);
+        println!("{}: {}", pattern, matches.len());
+        if show {
+            show_template(&puzzle, pattern, &template, &matches);
         }
     }
-
-    println!("P1: {}", count);
-    println!("P2: {}", count2);
 }
@@ -0,0 +1,382 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Div, Rem};
+use std::str::FromStr;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "RawMatrix<T>")
+)]
+pub struct Matrix<T> {
+    pub cols: usize,
+    pub data: Vec<T>,
+}
+
+// Unchecked shape straight from the deserializer, see TryFrom below
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawMatrix<T> {
+    cols: usize,
+    data: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<RawMatrix<T>> for Matrix<T> {
+    type Error = String;
+
+    fn try_from(raw: RawMatrix<T>) -> Result<Self, Self::Error> {
+        if !raw.data.len().is_multiple_of(raw.cols) {
+            return Err(format!(
+                "{} cells do not fill rows of {} columns",
+                raw.data.len(),
+                raw.cols
+            ));
+        }
+        Ok(Matrix {
+            cols: raw.cols,
+            data: raw.data,
+        })
+    }
+}
+
+impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
+    pub fn new(cols: usize, data: Vec<T>) -> Self {
+        Self { cols, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        if self.cols == 0 {
+            return 0;
+        }
+        self.data.len().div(self.cols)
+    }
+
+    // Returns T, pos, and direction from the origin
+    // i=x, k=y
+    pub fn neighbours(&self, pos: usize) -> Vec<Neighbour<T>> {
+        let ipos = pos as isize;
+        let (cur_x, cur_y) = self.icoords_from_pos(pos);
+        let icols = self.cols as isize;
+        let mut neighbours: Vec<Neighbour<T>> = Vec::new();
+        for i in -1..=1 {
+            for k in -1..=1 {
+                let new_row = cur_y + i;
+                let new_col = cur_x + k;
+                if !(i == 0 && k == 0)
+                    && new_row >= 0
+                    && new_row < self.rows() as isize
+                    && new_col >= 0
+                    && new_col < icols
+                {
+                    let new_pos = ipos + icols * i + k;
+                    let unew_pos = new_pos as usize;
+                    if let Some(n) = self.data.get(unew_pos) {
+                        let neighbour = Neighbour {
+                            c: *n,
+                            pos: unew_pos,
+                            x_dir: k,
+                            y_dir: i,
+                        };
+                        neighbours.push(neighbour)
+                    }
+                }
+            }
+        }
+        neighbours
+    }
+
+    pub fn get(&self, x: isize, y: isize) -> Option<T> {
+        if x >= 0 && y >= 0 && x < self.cols as isize && y < self.rows() as isize {
+            let pos = self.pos_from_coords(x as usize, y as usize);
+            if pos >= self.data.len() {
+                return None;
+            }
+            return Some(self.data[pos]);
+        }
+        None
+    }
+
+    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
+        self.cols * y + x
+    }
+
+    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
+        let x = pos.rem(self.cols);
+        let y = pos.div(self.cols);
+        (x, y)
+    }
+
+    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
+        let x = pos.rem(self.cols) as isize;
+        let y = pos.div(self.cols) as isize;
+        (x, y)
+    }
+
+    // Rows become columns, (x, y) -> (y, x)
+    pub fn transpose(&self) -> Self {
+        let rows = self.rows();
+        let mut data = Vec::with_capacity(self.data.len());
+        for x in 0..self.cols {
+            for y in 0..rows {
+                data.push(self.data[self.pos_from_coords(x, y)]);
+            }
+        }
+        Self::new(rows, data)
+    }
+
+    // Mirrors left to right
+    pub fn flip_horizontal(&self) -> Self {
+        let mut data = Vec::with_capacity(self.data.len());
+        for row in self.data.chunks(self.cols.max(1)) {
+            data.extend(row.iter().rev());
+        }
+        Self::new(self.cols, data)
+    }
+
+    // Mirrors top to bottom
+    pub fn flip_vertical(&self) -> Self {
+        let mut data = Vec::with_capacity(self.data.len());
+        for row in self.data.chunks(self.cols.max(1)).rev() {
+            data.extend(row.iter());
+        }
+        Self::new(self.cols, data)
+    }
+
+    // 90 degrees clockwise, the top row becomes the rightmost column
+    pub fn rotate_cw(&self) -> Self {
+        self.transpose().flip_horizontal()
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        let data = self.data.iter().rev().copied().collect();
+        Self::new(self.cols, data)
+    }
+
+    // 90 degrees counterclockwise (= 270 clockwise)
+    pub fn rotate_ccw(&self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    // Copies out a width x height block starting at (x, y)
+    // Returns None if the block does not fit inside the matrix
+    pub fn crop(&self, x: usize, y: usize, width: usize, height: usize) -> Option<Self> {
+        if x + width > self.cols || y + height > self.rows() {
+            return None;
+        }
+        let mut data = Vec::with_capacity(width * height);
+        for row in y..y + height {
+            let start = self.pos_from_coords(x, row);
+            data.extend_from_slice(&self.data[start..start + width]);
+        }
+        Some(Self::new(width, data))
+    }
+
+    // Writes other on top of self with its top left corner at (x, y)
+    // Returns false and leaves self untouched if other does not fit
+    pub fn paste(&mut self, x: usize, y: usize, other: &Matrix<T>) -> bool {
+        if x + other.cols > self.cols || y + other.rows() > self.rows() {
+            return false;
+        }
+        for (row, chunk) in other.data.chunks(other.cols.max(1)).enumerate() {
+            let start = self.pos_from_coords(x, y + row);
+            self.data[start..start + chunk.len()].copy_from_slice(chunk);
+        }
+        true
+    }
+}
+
+// One line per row with no separators, the same layout as the puzzle input
+// Parsing this back with str::parse gives the same matrix
+impl fmt::Display for Matrix<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.data.iter().enumerate() {
+            if i.rem(self.cols) == 0 && i >= self.cols {
+                writeln!(f)?;
+            }
+            write!(f, "{}", c)?;
+        }
+        Ok(())
+    }
+}
+
+impl Matrix<char> {
+    // Copy with only the given cells kept and everything else as '.',
+    // the way the puzzle text shows where the words are
+    pub fn reveal(&self, cells: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut revealed = Matrix::new(self.cols, vec!['.'; self.data.len()]);
+        for (x, y) in cells {
+            let pos = self.pos_from_coords(x, y);
+            revealed.data[pos] = self.data[pos];
+        }
+        revealed
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseMatrixError {
+    // Line numbers start from 1
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for ParseMatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseMatrixError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} cells, expected {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for ParseMatrixError {}
+
+impl FromStr for Matrix<char> {
+    type Err = ParseMatrixError;
+
+    // A single trailing newline is allowed so input files parse as is
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_suffix('\n').unwrap_or(s);
+        let mut data = Vec::new();
+        let mut cols: usize = 0;
+        if s.is_empty() {
+            return Ok(Matrix::new(cols, data));
+        }
+        for (i, line) in s.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let found = line.chars().count();
+            if i == 0 {
+                cols = found;
+            } else if found != cols {
+                return Err(ParseMatrixError::Ragged {
+                    line: i + 1,
+                    expected: cols,
+                    found,
+                });
+            }
+            data.extend(line.chars());
+        }
+        Ok(Matrix::new(cols, data))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Neighbour<T> {
+    pub c: T,
+    pub pos: usize,
+    pub x_dir: isize,
+    pub y_dir: isize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(rows: &str) -> Matrix<char> {
+        rows.parse().unwrap()
+    }
+
+    #[test]
+    fn rotates_and_flips_non_square() {
+        let m = grid("abc\ndef");
+        assert_eq!(m.transpose(), grid("ad\nbe\ncf"));
+        assert_eq!(m.rotate_cw(), grid("da\neb\nfc"));
+        assert_eq!(m.rotate_ccw(), grid("cf\nbe\nad"));
+        assert_eq!(m.rotate_180(), grid("fed\ncba"));
+        assert_eq!(m.flip_horizontal(), grid("cba\nfed"));
+        assert_eq!(m.flip_vertical(), grid("def\nabc"));
+        assert_eq!(m.rotate_cw().rotate_cw(), m.rotate_180());
+        assert_eq!(m.rotate_cw().rotate_ccw(), m);
+        assert_eq!(m.rotate_cw().rotate_cw().rotate_cw().rotate_cw(), m);
+        assert_eq!(m.transpose().transpose(), m);
+    }
+
+    #[test]
+    fn crop_and_paste_stay_inside() {
+        let mut m = grid("abcd\nefgh\nijkl");
+        assert_eq!(m.crop(1, 1, 3, 2), Some(grid("fgh\njkl")));
+        assert_eq!(m.crop(0, 0, 4, 3), Some(m.clone()));
+        assert_eq!(m.crop(2, 0, 3, 1), None);
+        assert_eq!(m.crop(0, 2, 1, 2), None);
+
+        let before = m.clone();
+        assert!(!m.paste(3, 0, &grid("XY")));
+        assert!(!m.paste(0, 2, &grid("X\nY")));
+        assert_eq!(m, before);
+        assert!(m.paste(2, 1, &grid("XY\nZW")));
+        assert_eq!(m, grid("abcd\nefXY\nijZW"));
+    }
+
+    #[test]
+    fn equal_matrices_hash_the_same() {
+        use std::collections::HashSet;
+        let m = grid("ab\ncd");
+        let mut seen = HashSet::new();
+        assert!(seen.insert(m.clone()));
+        assert!(!seen.insert(grid("ab\ncd")));
+        assert!(!seen.insert(m.rotate_cw().rotate_ccw()));
+        // Same cells in another shape are a different matrix
+        assert_ne!(grid("abcd"), m);
+        assert!(seen.insert(grid("abcd")));
+    }
+
+    #[test]
+    fn text_round_trips() {
+        for text in [
+            "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM",
+            "abc",
+            "a\nb\nc",
+            "X",
+            "",
+        ] {
+            let m = grid(text);
+            assert_eq!(m.to_string(), text);
+            assert_eq!(m.to_string().parse(), Ok(m));
+        }
+        // Input files as they are on disk
+        assert_eq!(grid("ab\r\ncd\r\n"), grid("ab\ncd"));
+        assert_eq!(grid("ab\ncd\n").rows(), 2);
+    }
+
+    #[test]
+    fn ragged_rows_are_rejected() {
+        let e = "abc\nabc\nab\nabc".parse::<Matrix<char>>().unwrap_err();
+        assert_eq!(
+            e,
+            ParseMatrixError::Ragged {
+                line: 3,
+                expected: 3,
+                found: 2
+            }
+        );
+        assert_eq!(e.to_string(), "line 3 has 2 cells, expected 3");
+        // Only one trailing newline belongs to the last row
+        assert!("ab\ncd\n\n".parse::<Matrix<char>>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_checks_the_shape() {
+        let m = grid("ab\ncd\nef");
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<Matrix<char>>(&json).unwrap(), m);
+
+        let e =
+            serde_json::from_str::<Matrix<char>>(r#"{"cols":4,"data":["a","b","c","d","e","f"]}"#)
+                .unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("6 cells do not fill rows of 4 columns"));
+    }
+}
@@ -0,0 +1,625 @@
+use crate::matrix::Matrix;
+use aho_corasick::AhoCorasick;
+use std::collections::HashSet;
+
+// Every direction a word can run in as (x, y) steps
+pub const DIRECTIONS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+pub fn direction_name(direction: (isize, isize)) -> &'static str {
+    match direction {
+        (1, 0) => "right",
+        (1, 1) => "down-right",
+        (0, 1) => "down",
+        (-1, 1) => "down-left",
+        (-1, 0) => "left",
+        (-1, -1) => "up-left",
+        (0, -1) => "up",
+        (1, -1) => "up-right",
+        _ => "?",
+    }
+}
+
+// How a word that reads the same backwards is counted
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Palindromes {
+    // Once per set of cells
+    Once,
+    // Once in each direction, the same way any other word is counted
+    Twice,
+}
+
+// How the grid is searched, both find the same matches
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Engine {
+    // From every cell in every direction
+    Walk,
+    // Every row, column and diagonal once, for many words or large grids
+    AhoCorasick,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    // Whether matches may share cells, otherwise the first match in reading order wins
+    pub overlaps: bool,
+    pub palindromes: Palindromes,
+    pub ignore_case: bool,
+    pub engine: Engine,
+    // Words run off one edge and back in from the opposite one, as on a torus
+    pub wrap: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            overlaps: true,
+            palindromes: Palindromes::Once,
+            ignore_case: false,
+            engine: Engine::Walk,
+            wrap: false,
+        }
+    }
+}
+
+// A word found in the grid, word is its index in the query
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Match {
+    pub word: usize,
+    pub x: usize,
+    pub y: usize,
+    pub direction: (isize, isize),
+}
+
+fn same(a: char, b: char, ignore_case: bool) -> bool {
+    a == b || (ignore_case && a.to_lowercase().eq(b.to_lowercase()))
+}
+
+// One letter standing for every letter it is the same as
+// Letters whose lowercase is longer than one letter only stand for themselves
+fn fold(c: char, ignore_case: bool) -> char {
+    if !ignore_case {
+        return c;
+    }
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) => l,
+        _ => c,
+    }
+}
+
+fn palindrome(word: &[char], ignore_case: bool) -> bool {
+    word.iter()
+        .zip(word.iter().rev())
+        .all(|(&a, &b)| same(a, b, ignore_case))
+}
+
+// Directions a word is counted in
+// A palindrome found in one direction is also found backwards from its
+// last letter, so the first half of the directions covers it.
+// A single letter is the same in every direction
+fn directions_for(word: &[char], options: &Options) -> &'static [(isize, isize)] {
+    if word.len() == 1 {
+        &DIRECTIONS[..1]
+    } else if palindrome(word, options.ignore_case) && options.palindromes == Palindromes::Once {
+        &DIRECTIONS[..4]
+    } else {
+        &DIRECTIONS[..]
+    }
+}
+
+// A row, column or diagonal as text, with the position of each letter
+// With wrap the line is a loop of period letters, repeated far enough that words
+// starting near its end can be read
+struct Line {
+    text: String,
+    cells: Vec<(usize, usize)>,
+    period: usize,
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Matrix<char> {
+    // Every occurrence of every word in reading order of the first letter,
+    // then in the order of DIRECTIONS and words
+    // With wrap a word may not use a cell twice, and the same cells read in the
+    // same order from another start or direction are only counted once
+    pub fn find_words(&self, words: &[&str], options: &Options) -> Vec<Match> {
+        let words: Vec<Vec<char>> = words.iter().map(|w| w.chars().collect()).collect();
+        let mut matches = match options.engine {
+            Engine::Walk => self.walk(&words, options),
+            Engine::AhoCorasick => self.aho_corasick(&words, options),
+        };
+        matches.retain(|m| directions_for(&words[m.word], options).contains(&m.direction));
+        if options.wrap {
+            matches = self.without_repeats(matches, &words, options);
+        }
+        if !options.overlaps {
+            matches = self.without_overlaps(matches, &words, options.wrap);
+        }
+        matches
+    }
+
+    pub fn count_words(&self, words: &[&str], options: &Options) -> usize {
+        self.find_words(words, options).len()
+    }
+
+    // Positions of the letters of a match for a word of length len, first letter first
+    pub fn word_cells(&self, m: &Match, len: usize, wrap: bool) -> Vec<(usize, usize)> {
+        (0..len)
+            .map(|i| {
+                let (x, y) = self.step(m.x, m.y, m.direction, i, wrap);
+                (x as usize, y as usize)
+            })
+            .collect()
+    }
+
+    // i steps from (x, y), which may be outside the grid without wrap
+    fn step(
+        &self,
+        x: usize,
+        y: usize,
+        direction: (isize, isize),
+        i: usize,
+        wrap: bool,
+    ) -> (isize, isize) {
+        let x = x as isize + direction.0 * i as isize;
+        let y = y as isize + direction.1 * i as isize;
+        if wrap {
+            (
+                x.rem_euclid(self.cols as isize),
+                y.rem_euclid(self.rows() as isize),
+            )
+        } else {
+            (x, y)
+        }
+    }
+
+    // Steps before a ray on the torus is back where it started
+    fn period(&self, direction: (isize, isize)) -> usize {
+        let cols = if direction.0 != 0 { self.cols } else { 1 };
+        let rows = if direction.1 != 0 { self.rows() } else { 1 };
+        cols / gcd(cols, rows) * rows
+    }
+
+    // Tries every word in every direction from every cell
+    fn walk(&self, words: &[Vec<char>], options: &Options) -> Vec<Match> {
+        let mut matches = Vec::new();
+        for pos in 0..self.data.len() {
+            let (x, y) = self.coords_from_pos(pos);
+            for direction in DIRECTIONS {
+                for (w, word) in words.iter().enumerate() {
+                    if word.is_empty() || (options.wrap && word.len() > self.period(direction)) {
+                        continue;
+                    }
+                    let found = Match {
+                        word: w,
+                        x,
+                        y,
+                        direction,
+                    };
+                    if self.reads(&found, word, options) {
+                        matches.push(found);
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    // Whether word is spelled out from the start of m in its direction
+    fn reads(&self, m: &Match, word: &[char], options: &Options) -> bool {
+        word.iter().enumerate().all(|(i, &letter)| {
+            let (x, y) = self.step(m.x, m.y, m.direction, i, options.wrap);
+            self.get(x, y)
+                .is_some_and(|c| same(c, letter, options.ignore_case))
+        })
+    }
+
+    // Reads every row, column and diagonal once with all the words at the same time.
+    // The lines only run in the first half of the directions, the other half is
+    // found by looking for the words backwards
+    fn aho_corasick(&self, words: &[Vec<char>], options: &Options) -> Vec<Match> {
+        // Each spelling once, with the words it stands for and whether it is reversed
+        let mut patterns: Vec<String> = Vec::new();
+        let mut owners: Vec<Vec<(usize, bool)>> = Vec::new();
+        for (w, word) in words.iter().enumerate() {
+            if word.is_empty() {
+                continue;
+            }
+            for reversed in [false, true] {
+                let spelling: String = if reversed {
+                    word.iter()
+                        .rev()
+                        .map(|&c| fold(c, options.ignore_case))
+                        .collect()
+                } else {
+                    word.iter().map(|&c| fold(c, options.ignore_case)).collect()
+                };
+                match patterns.iter().position(|p| *p == spelling) {
+                    Some(i) => owners[i].push((w, reversed)),
+                    None => {
+                        patterns.push(spelling);
+                        owners.push(vec![(w, reversed)]);
+                    }
+                }
+            }
+        }
+        if patterns.is_empty() {
+            return Vec::new();
+        }
+        let automaton = AhoCorasick::new(&patterns).expect("Failed to build the automaton");
+        let longest = words.iter().map(|w| w.len()).max().unwrap_or(0);
+
+        let mut matches = Vec::new();
+        for (dx, dy) in DIRECTIONS[..4].iter().copied() {
+            for line in self.lines((dx, dy), longest, options) {
+                // Byte offset of each letter, the automaton reports bytes
+                let offsets: Vec<usize> = line.text.char_indices().map(|(i, _)| i).collect();
+                for hit in automaton.find_overlapping_iter(&line.text) {
+                    let first = offsets
+                        .binary_search(&hit.start())
+                        .expect("Match does not start on a letter");
+                    // Later starts are the same as earlier ones once around the loop
+                    if first >= line.period {
+                        continue;
+                    }
+                    for &(w, reversed) in owners[hit.pattern().as_usize()].iter() {
+                        let len = words[w].len();
+                        if len > line.period {
+                            continue;
+                        }
+                        let ((x, y), direction) = if reversed {
+                            (line.cells[first + len - 1], (-dx, -dy))
+                        } else {
+                            (line.cells[first], (dx, dy))
+                        };
+                        matches.push(Match {
+                            word: w,
+                            x,
+                            y,
+                            direction,
+                        });
+                    }
+                }
+            }
+        }
+        // Back to the order walk finds them in
+        matches.sort_by_key(|m| {
+            (
+                self.pos_from_coords(m.x, m.y),
+                DIRECTIONS.iter().position(|&d| d == m.direction),
+                m.word,
+            )
+        });
+        matches
+    }
+
+    // Every line running in direction
+    // Without wrap a line starts at the edge, with wrap every loop of the torus is
+    // read once from its first cell in reading order
+    fn lines(&self, direction: (isize, isize), longest: usize, options: &Options) -> Vec<Line> {
+        let mut lines = Vec::new();
+        let mut seen = vec![false; self.data.len()];
+        for pos in 0..self.data.len() {
+            let (x, y) = self.coords_from_pos(pos);
+            let length = if options.wrap {
+                if seen[pos] {
+                    continue;
+                }
+                let period = self.period(direction);
+                period + longest.saturating_sub(1)
+            } else {
+                if self
+                    .get(x as isize - direction.0, y as isize - direction.1)
+                    .is_some()
+                {
+                    continue;
+                }
+                usize::MAX
+            };
+
+            let mut text = String::new();
+            let mut cells = Vec::new();
+            for i in 0..length {
+                let (cx, cy) = self.step(x, y, direction, i, options.wrap);
+                let Some(c) = self.get(cx, cy) else {
+                    break;
+                };
+                text.push(fold(c, options.ignore_case));
+                cells.push((cx as usize, cy as usize));
+                seen[self.pos_from_coords(cx as usize, cy as usize)] = true;
+            }
+            let period = if options.wrap {
+                self.period(direction)
+            } else {
+                cells.len()
+            };
+            lines.push(Line {
+                text,
+                cells,
+                period,
+            });
+        }
+        lines
+    }
+
+    // The same word over the same cells is kept once, in the first place found
+    // A palindrome read backwards over the same cells is the same match unless
+    // palindromes are counted twice
+    fn without_repeats(
+        &self,
+        matches: Vec<Match>,
+        words: &[Vec<char>],
+        options: &Options,
+    ) -> Vec<Match> {
+        let mut seen = HashSet::new();
+        matches
+            .into_iter()
+            .filter(|m| {
+                let word = &words[m.word];
+                let mut cells = self.word_cells(m, word.len(), true);
+                if options.palindromes == Palindromes::Once && palindrome(word, options.ignore_case)
+                {
+                    let reversed: Vec<(usize, usize)> = cells.iter().rev().copied().collect();
+                    cells = cells.min(reversed);
+                }
+                seen.insert((m.word, cells))
+            })
+            .collect()
+    }
+
+    // Keeps the first match in order and drops any later one sharing a cell with it
+    fn without_overlaps(&self, matches: Vec<Match>, words: &[Vec<char>], wrap: bool) -> Vec<Match> {
+        let mut used = vec![false; self.data.len()];
+        let mut kept = Vec::new();
+        for m in matches {
+            let cells: Vec<usize> = self
+                .word_cells(&m, words[m.word].len(), wrap)
+                .into_iter()
+                .map(|(x, y)| self.pos_from_coords(x, y))
+                .collect();
+            if cells.iter().any(|&c| used[c]) {
+                continue;
+            }
+            for c in cells {
+                used[c] = true;
+            }
+            kept.push(m);
+        }
+        kept
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+                           XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX";
+
+    fn grid(rows: &str) -> Matrix<char> {
+        rows.parse().unwrap()
+    }
+
+    fn count(grid: &Matrix<char>, words: &[&str], options: Options) -> usize {
+        let walk = grid.find_words(words, &options);
+        let fast = grid.find_words(
+            words,
+            &Options {
+                engine: Engine::AhoCorasick,
+                ..options
+            },
+        );
+        assert_eq!(walk, fast);
+        walk.len()
+    }
+
+    #[test]
+    fn engines_agree_on_the_example() {
+        // Mixed case and palindromes, including ones that are part of other words
+        let example = grid(&EXAMPLE.replace("XMAS", "xmAs"));
+        let words = ["XMAS", "xmas", "SAMX", "MAM", "AMA", "SXS", "MMM"];
+        for overlaps in [true, false] {
+            for palindromes in [Palindromes::Once, Palindromes::Twice] {
+                for ignore_case in [false, true] {
+                    let options = Options {
+                        overlaps,
+                        palindromes,
+                        ignore_case,
+                        ..Options::default()
+                    };
+                    let walk = example.find_words(&words, &options);
+                    let fast = example.find_words(
+                        &words,
+                        &Options {
+                            engine: Engine::AhoCorasick,
+                            ..options
+                        },
+                    );
+                    assert!(!walk.is_empty());
+                    assert_eq!(walk, fast, "{:?}", options);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn counts_the_example() {
+        let example = grid(EXAMPLE);
+        assert_eq!(count(&example, &["XMAS"], Options::default()), 18);
+        assert_eq!(example.count_words(&["XMAS"], &Options::default()), 18);
+        // Every XMAS is a SAMX read from its other end
+        assert_eq!(count(&example, &["XMAS", "SAMX"], Options::default()), 36);
+        let apart = Options {
+            overlaps: false,
+            ..Options::default()
+        };
+        assert_eq!(count(&example, &["XMAS"], apart), 9);
+    }
+
+    #[test]
+    fn overlapping_words_count_once_without_overlaps() {
+        // Forwards from the left and backwards from the right, sharing the S
+        let row = grid("XMASAMX");
+        let apart = Options {
+            overlaps: false,
+            ..Options::default()
+        };
+        assert_eq!(count(&row, &["XMAS"], Options::default()), 2);
+        assert_eq!(count(&row, &["XMAS"], apart), 1);
+        assert_eq!(row.find_words(&["XMAS"], &apart)[0].x, 0);
+        assert_eq!(count(&grid("XMASXMAS"), &["XMAS"], apart), 2);
+    }
+
+    #[test]
+    fn palindromes_once_or_twice() {
+        let square = grid("ABA\nB.B\nABA");
+        let twice = Options {
+            palindromes: Palindromes::Twice,
+            ..Options::default()
+        };
+        // Two rows and two columns
+        assert_eq!(count(&square, &["ABA"], Options::default()), 4);
+        assert_eq!(count(&square, &["ABA"], twice), 8);
+        // Words that are not palindromes are the same either way
+        assert_eq!(count(&square, &["AB"], Options::default()), 8);
+        assert_eq!(count(&square, &["AB"], twice), 8);
+    }
+
+    #[test]
+    fn ignores_case_without_wrap() {
+        let mixed = grid("xMaS\n.m..\n..A.\n...s");
+        let ignore_case = Options {
+            ignore_case: true,
+            ..Options::default()
+        };
+        assert_eq!(count(&mixed, &["XMAS"], Options::default()), 0);
+        assert_eq!(count(&mixed, &["XMAS"], ignore_case), 2);
+        assert_eq!(count(&mixed, &["xmas"], ignore_case), 2);
+
+        let lower = grid(&EXAMPLE.to_lowercase());
+        assert_eq!(count(&lower, &["XMAS"], Options::default()), 0);
+        assert_eq!(count(&lower, &["XMAS"], ignore_case), 18);
+    }
+
+    #[test]
+    fn reveals_the_example_as_the_puzzle_shows_it() {
+        let example = grid(EXAMPLE);
+        let found = example.find_words(&["XMAS"], &Options::default());
+        let cells = found.iter().flat_map(|m| example.word_cells(m, 4, false));
+        let dotted = "....XXMAS.\n.SAMXMS...\n...S..A...\n..A.A.MS.X\nXMASAMX.MM\n\
+                      X.....XA.A\nS.S.S.S.SS\n.A.A.A.A.A\n..M.M.M.MM\n.X.X.XMASX";
+        assert_eq!(example.reveal(cells), grid(dotted));
+        assert_eq!(
+            example.reveal([]).to_string(),
+            EXAMPLE.replace(|c| c != '\n', ".")
+        );
+    }
+
+    #[test]
+    fn names_every_direction() {
+        let names: Vec<&str> = DIRECTIONS.iter().map(|&d| direction_name(d)).collect();
+        assert_eq!(
+            names,
+            vec![
+                "right",
+                "down-right",
+                "down",
+                "down-left",
+                "left",
+                "up-left",
+                "up",
+                "up-right"
+            ]
+        );
+        assert_eq!(direction_name((2, 0)), "?");
+    }
+
+    fn wrap() -> Options {
+        Options {
+            wrap: true,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn words_run_over_each_edge() {
+        // Across the right edge
+        let across = grid("ASXM\n....\n....");
+        assert_eq!(count(&across, &["XMAS"], Options::default()), 0);
+        assert_eq!(count(&across, &["XMAS"], wrap()), 1);
+        let found = across.find_words(&["XMAS"], &wrap());
+        assert_eq!(
+            across.word_cells(&found[0], 4, true),
+            vec![(2, 0), (3, 0), (0, 0), (1, 0)]
+        );
+
+        // Up over the top edge
+        let up = grid("A...\nM...\nX...\nS...\n");
+        assert_eq!(count(&up, &["XMAS"], wrap()), 1);
+        assert_eq!(up.find_words(&["XMAS"], &wrap())[0].direction, (0, -1));
+
+        // Diagonally out of the bottom right corner
+        let diagonal = grid("..A..\n...S.\n.....\nX....\n.M...");
+        assert_eq!(count(&diagonal, &["XMAS"], Options::default()), 0);
+        assert_eq!(count(&diagonal, &["XMAS"], wrap()), 1);
+        assert_eq!(diagonal.find_words(&["XMAS"], &wrap())[0].direction, (1, 1));
+    }
+
+    #[test]
+    fn words_do_not_loop_onto_themselves() {
+        // XMASXMAS would go round the row twice
+        let row = grid("XMAS");
+        assert_eq!(count(&row, &["XMAS"], wrap()), 1);
+        assert_eq!(count(&row, &["XMASX"], wrap()), 0);
+        assert_eq!(count(&row, &["SXMA", "ASXM", "MASX"], wrap()), 3);
+
+        // Going right or left over two cells visits them in the same order,
+        // and so do the diagonals on a single row
+        let pair = grid("AB");
+        assert_eq!(count(&pair, &["AB"], wrap()), 1);
+        assert_eq!(count(&pair, &["AB"], Options::default()), 1);
+        assert_eq!(count(&pair, &["ABA"], wrap()), 0);
+    }
+
+    #[test]
+    fn palindromes_on_a_torus() {
+        let pair = grid("AA");
+        let twice = Options {
+            palindromes: Palindromes::Twice,
+            ..wrap()
+        };
+        // Starting from either cell, each read both ways
+        assert_eq!(count(&pair, &["AA"], wrap()), 1);
+        assert_eq!(count(&pair, &["AA"], twice), 2);
+
+        let ring = grid("ABA.");
+        assert_eq!(count(&ring, &["ABA"], wrap()), 1);
+        assert_eq!(count(&ring, &["ABA"], twice), 2);
+        assert_eq!(count(&grid("AB.A"), &["AA"], wrap()), 1);
+    }
+
+    #[test]
+    fn wrap_finds_everything_the_flat_search_does() {
+        let example = grid(EXAMPLE);
+        let flat = example.find_words(&["XMAS"], &Options::default());
+        let wrapped = example.find_words(&["XMAS"], &wrap());
+        assert_eq!(flat.len(), 18);
+        assert!(flat.iter().all(|m| wrapped.contains(m)));
+        assert_eq!(count(&example, &["XMAS"], wrap()), 21);
+    }
+}
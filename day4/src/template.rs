@@ -0,0 +1,296 @@
+use crate::matrix::{Matrix, ParseMatrixError};
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// Template cell that matches any letter
+pub const WILDCARD: char = '.';
+
+// The 8 ways to turn and mirror a square
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transform {
+    Identity,
+    RotateCw,
+    Rotate180,
+    RotateCcw,
+    FlipHorizontal,
+    FlipVertical,
+    // Mirrored along the top left to bottom right diagonal
+    Transpose,
+    // Mirrored along the other diagonal
+    AntiTranspose,
+}
+
+pub const TRANSFORMS: [Transform; 8] = [
+    Transform::Identity,
+    Transform::RotateCw,
+    Transform::Rotate180,
+    Transform::RotateCcw,
+    Transform::FlipHorizontal,
+    Transform::FlipVertical,
+    Transform::Transpose,
+    Transform::AntiTranspose,
+];
+
+impl Transform {
+    pub fn apply(&self, matrix: &Matrix<char>) -> Matrix<char> {
+        match self {
+            Transform::Identity => matrix.clone(),
+            Transform::RotateCw => matrix.rotate_cw(),
+            Transform::Rotate180 => matrix.rotate_180(),
+            Transform::RotateCcw => matrix.rotate_ccw(),
+            Transform::FlipHorizontal => matrix.flip_horizontal(),
+            Transform::FlipVertical => matrix.flip_vertical(),
+            Transform::Transpose => matrix.transpose(),
+            Transform::AntiTranspose => matrix.transpose().rotate_180(),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Transform::Identity => write!(f, "as is"),
+            Transform::RotateCw => write!(f, "rotated clockwise"),
+            Transform::Rotate180 => write!(f, "rotated 180"),
+            Transform::RotateCcw => write!(f, "rotated counterclockwise"),
+            Transform::FlipHorizontal => write!(f, "flipped horizontally"),
+            Transform::FlipVertical => write!(f, "flipped vertically"),
+            Transform::Transpose => write!(f, "transposed"),
+            Transform::AntiTranspose => write!(f, "anti-transposed"),
+        }
+    }
+}
+
+// Small 2D pattern where WILDCARD cells match anything
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Template {
+    pub pattern: Matrix<char>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseTemplateError {
+    // Nothing but wildcards, which would match everywhere
+    NoLetters,
+    Matrix(ParseMatrixError),
+}
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseTemplateError::NoLetters => write!(f, "template has no letters to match"),
+            ParseTemplateError::Matrix(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ParseTemplateError {}
+
+impl From<ParseMatrixError> for ParseTemplateError {
+    fn from(e: ParseMatrixError) -> Self {
+        ParseTemplateError::Matrix(e)
+    }
+}
+
+// Rows are separated by '/' or newlines, e.g. "M.S/.A./M.S"
+impl FromStr for Template {
+    type Err = ParseTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let pattern: Matrix<char> = s.replace('/', "\n").parse()?;
+        if pattern.data.iter().all(|&c| c == WILDCARD) {
+            return Err(ParseTemplateError::NoLetters);
+        }
+        Ok(Template { pattern })
+    }
+}
+
+impl Template {
+    // The pattern in every orientation, or just as given
+    // Orientations that come out the same are only kept once
+    pub fn variants(&self, orientations: bool) -> Vec<(Transform, Matrix<char>)> {
+        let transforms = if orientations {
+            &TRANSFORMS[..]
+        } else {
+            &TRANSFORMS[..1]
+        };
+        let mut variants: Vec<(Transform, Matrix<char>)> = Vec::new();
+        for transform in transforms {
+            let variant = transform.apply(&self.pattern);
+            if !variants.iter().any(|(_, v)| *v == variant) {
+                variants.push((*transform, variant));
+            }
+        }
+        variants
+    }
+
+    // Grid positions of the letters, not the wildcards, of a match
+    pub fn cells(&self, m: &TemplateMatch) -> Vec<(usize, usize)> {
+        let variant = m.transform.apply(&self.pattern);
+        variant
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, &c)| c != WILDCARD)
+            .map(|(i, _)| {
+                let (x, y) = variant.coords_from_pos(i);
+                (m.x + x, m.y + y)
+            })
+            .collect()
+    }
+}
+
+// Top left corner of a match and the orientation of the template that matched
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TemplateMatch {
+    pub x: usize,
+    pub y: usize,
+    pub transform: Transform,
+}
+
+impl Matrix<char> {
+    // Every position where the template, or one of its orientations, fits
+    pub fn match_template(&self, template: &Template, orientations: bool) -> Vec<TemplateMatch> {
+        let variants = template.variants(orientations);
+        let mut matches = Vec::new();
+        for y in 0..self.rows() {
+            for x in 0..self.cols {
+                for (transform, variant) in variants.iter() {
+                    if self.fits(x, y, variant) {
+                        matches.push(TemplateMatch {
+                            x,
+                            y,
+                            transform: *transform,
+                        });
+                    }
+                }
+            }
+        }
+        matches
+    }
+
+    fn fits(&self, x: usize, y: usize, pattern: &Matrix<char>) -> bool {
+        if x + pattern.cols > self.cols || y + pattern.rows() > self.rows() {
+            return false;
+        }
+        pattern.data.iter().enumerate().all(|(i, &c)| {
+            let (px, py) = pattern.coords_from_pos(i);
+            c == WILDCARD || self.data[self.pos_from_coords(x + px, y + py)] == c
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "MMMSXXMASM\nMSAMXMSMSA\nAMXSXMAAMM\nMSAMASMSMX\nXMASAMXAMM\n\
+                           XXAMMXXAMA\nSMSMSASXSS\nSAXAMASAAA\nMAMMMXMMMM\nMXMXAXMASX";
+
+    fn grid(rows: &str) -> Matrix<char> {
+        rows.parse().unwrap()
+    }
+
+    #[test]
+    fn finds_every_x_mas_in_the_example() {
+        let example = grid(EXAMPLE);
+        let x_mas: Template = "M.S/.A./M.S".parse().unwrap();
+        let matches = example.match_template(&x_mas, true);
+        assert_eq!(matches.len(), 9);
+        let corners: Vec<(usize, usize)> = matches.iter().map(|m| (m.x, m.y)).collect();
+        assert_eq!(
+            corners,
+            vec![
+                (1, 0),
+                (5, 1),
+                (6, 1),
+                (1, 2),
+                (3, 2),
+                (0, 6),
+                (2, 6),
+                (4, 6),
+                (6, 6)
+            ]
+        );
+        // Without orientations only the ones the right way up are left
+        let as_is: Vec<TemplateMatch> = matches
+            .into_iter()
+            .filter(|m| m.transform == Transform::Identity)
+            .collect();
+        assert_eq!(as_is.len(), 2);
+        assert_eq!(example.match_template(&x_mas, false), as_is);
+    }
+
+    #[test]
+    fn rejects_templates_without_letters() {
+        assert_eq!("".parse::<Template>(), Err(ParseTemplateError::NoLetters));
+        assert_eq!(
+            "X/..".parse::<Template>(),
+            Err(ParseTemplateError::Matrix(ParseMatrixError::Ragged {
+                line: 2,
+                expected: 1,
+                found: 2
+            }))
+        );
+        assert_eq!(
+            "../..".parse::<Template>(),
+            Err(ParseTemplateError::NoLetters)
+        );
+    }
+
+    #[test]
+    fn same_orientations_are_kept_once() {
+        let count = |pattern: &str, orientations: bool| {
+            pattern
+                .parse::<Template>()
+                .unwrap()
+                .variants(orientations)
+                .len()
+        };
+        assert_eq!(count("M.S/.A./M.S", true), 4);
+        assert_eq!(count("XMAS", true), 4);
+        assert_eq!(count("XMAS", false), 1);
+        assert_eq!(count("A", true), 1);
+        assert_eq!(count("AB/CD", true), 8);
+    }
+
+    #[test]
+    fn anti_transpose_mirrors_along_the_other_diagonal() {
+        let m = grid("abc\ndef");
+        assert_eq!(Transform::AntiTranspose.apply(&m), grid("fc\neb\nda"));
+        // Its own inverse, and a transpose turned half way round
+        assert_eq!(
+            Transform::AntiTranspose.apply(&Transform::AntiTranspose.apply(&m)),
+            m
+        );
+        assert_eq!(
+            Transform::AntiTranspose.apply(&m),
+            Transform::Rotate180.apply(&Transform::Transpose.apply(&m))
+        );
+    }
+
+    #[test]
+    fn cells_skip_the_wildcards() {
+        let template: Template = "M.S/.A./M.S".parse().unwrap();
+        let m = TemplateMatch {
+            x: 1,
+            y: 2,
+            transform: Transform::Identity,
+        };
+        assert_eq!(
+            template.cells(&m),
+            vec![(1, 2), (3, 2), (2, 3), (1, 4), (3, 4)]
+        );
+        let turned = TemplateMatch {
+            x: 0,
+            y: 2,
+            transform: Transform::RotateCw,
+        };
+        let grid = grid(".....\n.....\nM.M..\n.A...\nS.S..");
+        assert_eq!(grid.match_template(&template, true), vec![turned]);
+        assert_eq!(
+            template.cells(&turned),
+            vec![(0, 2), (2, 2), (1, 3), (0, 4), (2, 4)]
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use crate::rules::Rule;
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Section {
+    Rules,
+    Updates,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Section::Rules => write!(f, "rules"),
+            Section::Updates => write!(f, "updates"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    // Rule without the '|'
+    Rule(String),
+    Number(String, ParseIntError),
+}
+
+// Line numbers start from 1 and count from the top of the file the section is in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub section: Section,
+    pub line: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} line {}: ", self.section, self.line)?;
+        match &self.kind {
+            ParseErrorKind::Rule(line) => write!(f, "expected before|after, got {:?}", line),
+            ParseErrorKind::Number(x, e) => write!(f, "invalid page {:?}: {}", x, e),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Default)]
+pub struct Input {
+    pub rules: Vec<Rule>,
+    pub updates: Vec<Vec<usize>>,
+}
+
+impl Input {
+    // The puzzle input as is, rules and updates separated by a blank line
+    pub fn parse(text: &str) -> Result<Input, ParseError> {
+        let mut lines = text.lines().enumerate();
+        let rules: Vec<(usize, &str)> = lines
+            .by_ref()
+            .take_while(|(_, line)| !line.trim().is_empty())
+            .collect();
+        let updates: Vec<(usize, &str)> = lines.collect();
+        Ok(Input {
+            rules: parse_rules(rules)?,
+            updates: parse_updates(updates)?,
+        })
+    }
+
+    // Rules and updates from files of their own
+    pub fn parse_sections(rules: &str, updates: &str) -> Result<Input, ParseError> {
+        Ok(Input {
+            rules: parse_rules(rules.lines().enumerate())?,
+            updates: parse_updates(updates.lines().enumerate())?,
+        })
+    }
+}
+
+fn page(x: &str, section: Section, line: usize) -> Result<usize, ParseError> {
+    x.trim().parse().map_err(|e| ParseError {
+        section,
+        line,
+        kind: ParseErrorKind::Number(x.to_string(), e),
+    })
+}
+
+// Lines come numbered from 0, blank ones are skipped
+fn parse_rules<'a>(
+    lines: impl IntoIterator<Item = (usize, &'a str)>,
+) -> Result<Vec<Rule>, ParseError> {
+    let mut rules = Vec::new();
+    for (i, line) in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (before, after) = line.split_once('|').ok_or_else(|| ParseError {
+            section: Section::Rules,
+            line: i + 1,
+            kind: ParseErrorKind::Rule(line.to_string()),
+        })?;
+        rules.push(Rule {
+            before: page(before, Section::Rules, i + 1)?,
+            after: page(after, Section::Rules, i + 1)?,
+            line: i + 1,
+        });
+    }
+    Ok(rules)
+}
+
+fn parse_updates<'a>(
+    lines: impl IntoIterator<Item = (usize, &'a str)>,
+) -> Result<Vec<Vec<usize>>, ParseError> {
+    let mut updates = Vec::new();
+    for (i, line) in lines {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let update = line
+            .split(',')
+            .map(|x| page(x, Section::Updates, i + 1))
+            .collect::<Result<Vec<usize>, _>>()?;
+        updates.push(update);
+    }
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combined_and_separate_files_agree() {
+        let combined = Input::parse("47|53\r\n97|13\n\n75,47,61\n97,13\n").unwrap();
+        let separate = Input::parse_sections("47|53\n97|13\n", "75,47,61\n97,13").unwrap();
+        assert_eq!(combined.rules, separate.rules);
+        assert_eq!(combined.updates, separate.updates);
+        assert_eq!(combined.updates, vec![vec![75, 47, 61], vec![97, 13]]);
+    }
+
+    #[test]
+    fn errors_name_the_section_and_line() {
+        let e = Input::parse("47|53\n97-13\n\n75,47\n").unwrap_err();
+        assert_eq!((e.section, e.line), (Section::Rules, 2));
+        assert_eq!(
+            e.to_string(),
+            "rules line 2: expected before|after, got \"97-13\""
+        );
+
+        // Lines count from the top of the file, not from the blank line
+        let e = Input::parse("47|53\n\n75,47\n75,,47\n").unwrap_err();
+        assert_eq!((e.section, e.line), (Section::Updates, 4));
+        let e = Input::parse_sections("47|53\n", "75,47\n75,,47\n").unwrap_err();
+        assert_eq!((e.section, e.line), (Section::Updates, 2));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod input;
+pub mod ordering;
+pub mod rules;
+pub mod validate;
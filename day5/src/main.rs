@@ -1,119 +1,142 @@
-use std::collections::HashMap;
+use day5::input::{Input, Section};
+use day5::ordering::PageOrder;
+use day5::rules::{explain, in_order, repair};
+use day5::validate::validate;
 use std::env;
-use std::fs::File;
 use std::io::{self, BufRead};
 use std::ops::Div;
-use std::path::Path;
 
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
-}
-// Returns (true, 0) for allowed updates
-// Returns (false, i) for disallowed updates (pointing the fault)
-// true and false are reversed for reasons unknown to me
-fn is_allowed(update: &Vec<usize>, rules: &HashMap<usize, Vec<usize>>) -> (bool, Vec<usize>) {
-    let mut checked: HashMap<usize, bool> = HashMap::new();
-    let mut faults: Vec<usize> = Vec::new();
-    let mut faulty = true;
-    for (i, page) in update.iter().enumerate() {
-        if let Some(&ref ruleset) = rules.get(&page) {
-            let checklist: Vec<&usize> = ruleset.iter().filter(|x| update.contains(x)).collect();
-            if !checklist.iter().all(|key| checked.contains_key(key)) {
-                faults.push(i);
-                faulty = false;
-            }
-        }
-        checked.insert(*page, true);
+// How the rules order two pages, directly or through a chain of rules
+fn answer(order: &PageOrder, closure: &PageOrder, a: usize, b: usize) -> String {
+    match (order.must_precede(a, b), order.must_precede(b, a)) {
+        (true, true) => format!("{}|{} and {}|{} contradict", a, b, b, a),
+        (true, false) => format!("{}|{}", a, b),
+        (false, true) => format!("{}|{}", b, a),
+        (false, false) => match (closure.must_precede(a, b), closure.must_precede(b, a)) {
+            (true, true) => format!("{} and {} loop through other pages", a, b),
+            (true, false) => format!("{}|{} through other pages", a, b),
+            (false, true) => format!("{}|{} through other pages", b, a),
+            (false, false) => format!("no rule orders {} and {}", a, b),
+        },
     }
-    return (faulty, faults);
 }
 
-// Returns true if the update should be retained for P2
-// Updates sum to count the score for P1
-fn check(update: &Vec<usize>, rules: &HashMap<usize, Vec<usize>>, sum: &mut usize) -> bool {
-    let allowed = is_allowed(&update, &rules);
-    match allowed {
-        (true, _) => {
-            *sum += *update.get(update.len().div(2)).unwrap();
-            return false;
-        }
-        _ => {
-            return true;
+// One "a b" pair per line from stdin
+fn query(order: &PageOrder) {
+    let closure = order.closure();
+    for (i, line) in io::stdin().lock().lines().map_while(Result::ok).enumerate() {
+        let pages: Vec<usize> = line
+            .split_whitespace()
+            .map_while(|x| x.parse().ok())
+            .collect();
+        match pages[..] {
+            [a, b] => println!("{}", answer(order, &closure, a, b)),
+            _ => eprintln!("stdin line {}: expected two pages, got {:?}", i + 1, line),
         }
     }
 }
 
-fn check_and_repair(update: &mut Vec<usize>, rules: &HashMap<usize, Vec<usize>>) -> usize {
-    let (allowed, faults) = is_allowed(&update, &rules);
-    match allowed {
-        false => {
-            for fault in faults.iter().rev() {
-                update.swap(*fault, *fault + 1);
-            }
-
-            check_and_repair(update, rules)
-        }
-        true => {
-            return *update.get(update.len().div(2)).unwrap();
-        }
-    }
+fn usage(program: &str) -> ! {
+    eprintln!(
+        "Usage: {} <input> | <rules> <updates> [--validate] [--explain] [--query]",
+        program
+    );
+    std::process::exit(1);
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
-    let rulefile = &args[1];
-    let updatefile = &args[2];
-
-    let mut rules: HashMap<usize, Vec<usize>> = HashMap::new();
-    let mut updates: Vec<Vec<usize>> = Vec::new();
+    if args.len() < 2 {
+        usage(&args[0]);
+    }
+    // Either the puzzle input or the rules and updates in two files
+    let (files, options) = match args.get(2) {
+        Some(updates) if !updates.starts_with("--") => (&args[1..3], &args[3..]),
+        _ => (&args[1..2], &args[2..]),
+    };
 
-    // Read rules
-    if let Ok(lines) = read_lines(rulefile) {
-        for line in lines.flatten() {
-            let rule: Vec<usize> = line
-                .split("|")
-                .collect::<Vec<&str>>()
-                .iter()
-                .map(|x| x.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>();
-            match rules.get_mut(&rule[1]) {
-                Some(val) if !val.contains(&rule[1]) => val.push(rule[0]),
-                None => {
-                    rules.insert(rule[1], vec![rule[0]]);
-                    ()
-                }
-                _ => {}
-            }
+    let mut validating = false;
+    let mut explaining = false;
+    let mut querying = false;
+    for option in options {
+        match option.as_str() {
+            "--validate" => validating = true,
+            "--explain" => explaining = true,
+            "--query" => querying = true,
+            _ => usage(&args[0]),
         }
     }
 
-    // Read updates
-    if let Ok(lines) = read_lines(updatefile) {
-        for line in lines.flatten() {
-            let update: Vec<usize> = line
-                .split(",")
-                .collect::<Vec<&str>>()
-                .iter()
-                .map(|x| x.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>();
-            updates.push(update);
+    let read = |filename: &String| {
+        std::fs::read_to_string(filename).unwrap_or_else(|e| {
+            eprintln!("{}: {}", filename, e);
+            std::process::exit(1);
+        })
+    };
+    let parsed = match files {
+        [input] => Input::parse(&read(input)),
+        [rules, updates] => Input::parse_sections(&read(rules), &read(updates)),
+        _ => unreachable!(),
+    };
+    let Input {
+        rules: rule_list,
+        updates,
+    } = match parsed {
+        Ok(input) => input,
+        Err(e) => {
+            let file = match e.section {
+                Section::Updates => files.last(),
+                Section::Rules => files.first(),
+            };
+            eprintln!("{}: {}", file.unwrap_or(&args[1]), e);
+            std::process::exit(1);
+        }
+    };
+    let rules = match PageOrder::new(&rule_list) {
+        Ok(order) => order,
+        Err(e) => {
+            eprintln!("{}: {}", files[0], e);
+            std::process::exit(1);
+        }
+    };
+
+    if validating {
+        for problem in validate(&rule_list, &updates) {
+            println!("{}", problem);
         }
     }
 
+    if querying {
+        query(&rules);
+        return;
+    }
+
+    // Updates in order count for P1, the rest are kept for P2
     let mut p1_sum: usize = 0;
-    updates.retain(|mut x| check(&mut x, &rules, &mut p1_sum));
+    let mut rejected: Vec<Vec<usize>> = Vec::new();
+    for (i, update) in updates.into_iter().enumerate() {
+        if in_order(&update, &rules) {
+            p1_sum += update[update.len().div(2)];
+            continue;
+        }
+        if explaining {
+            let pages: Vec<String> = update.iter().map(|p| p.to_string()).collect();
+            println!("Update {} ({}) breaks:", i + 1, pages.join(","));
+            for violation in explain(&update, &rules) {
+                println!("  {}", violation);
+            }
+        }
+        rejected.push(update);
+    }
     println!("P1: {}", p1_sum);
 
-    let p2: usize = updates
-        .into_iter()
-        .map(|mut x| check_and_repair(&mut x, &rules))
-        .collect::<Vec<usize>>()
-        .iter()
-        .fold(0, |sum, e| sum + e);
+    let mut p2: usize = 0;
+    for update in rejected.iter() {
+        match repair(update, &rules) {
+            Ok(repaired) => p2 += repaired[repaired.len().div(2)],
+            Err(cycle) => eprintln!("Cannot repair {:?}, the rules loop: {}", update, cycle),
+        }
+    }
 
     println!("P2: {}", p2);
 }
@@ -0,0 +1,129 @@
+use crate::rules::{Precedence, Rule};
+use std::error::Error;
+use std::fmt;
+
+// Pages are numbered below this
+pub const PAGES: usize = 100;
+
+// The rules as one bit per pair of pages, bit b of after[a] is set for a rule a|b
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PageOrder {
+    after: [u128; PAGES],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageOutOfRange {
+    pub rule: Rule,
+}
+
+impl fmt::Display for PageOutOfRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rules line {}: {}|{} has a page above {}",
+            self.rule.line,
+            self.rule.before,
+            self.rule.after,
+            PAGES - 1
+        )
+    }
+}
+
+impl Error for PageOutOfRange {}
+
+impl PageOrder {
+    pub fn new(rules: &[Rule]) -> Result<Self, PageOutOfRange> {
+        let mut after = [0; PAGES];
+        for rule in rules {
+            if rule.before >= PAGES || rule.after >= PAGES {
+                return Err(PageOutOfRange { rule: *rule });
+            }
+            after[rule.before] |= 1 << rule.after;
+        }
+        Ok(PageOrder { after })
+    }
+
+    // Whether a rule says before comes before after, O(1)
+    // Pages out of range have no rules
+    pub fn must_precede(&self, before: usize, after: usize) -> bool {
+        before < PAGES && after < PAGES && self.after[before] & (1 << after) != 0
+    }
+
+    // Every page that has to come after page
+    pub fn successors(&self, page: usize) -> impl Iterator<Item = usize> + '_ {
+        (0..PAGES).filter(move |&other| self.must_precede(page, other))
+    }
+
+    // a|b whenever a chain of rules a|x, x|y, ..., z|b exists.
+    // Only rules between the pages of an update count for the puzzle, so this is for
+    // questions about the rules themselves. If the rules loop, pages in the loop
+    // come before each other and themselves
+    pub fn closure(&self) -> Self {
+        let mut after = self.after;
+        for via in 0..PAGES {
+            for page in 0..PAGES {
+                if after[page] & (1 << via) != 0 {
+                    after[page] |= after[via];
+                }
+            }
+        }
+        PageOrder { after }
+    }
+}
+
+impl Precedence for PageOrder {
+    fn must_precede(&self, before: usize, after: usize) -> bool {
+        PageOrder::must_precede(self, before, after)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{explain, repair, rule_map};
+
+    fn rules(text: &str) -> Vec<Rule> {
+        text.split_whitespace()
+            .enumerate()
+            .map(|(i, rule)| {
+                let (before, after) = rule.split_once('|').unwrap();
+                Rule {
+                    before: before.parse().unwrap(),
+                    after: after.parse().unwrap(),
+                    line: i + 1,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn agrees_with_the_rule_map() {
+        let rules = rules("47|53 97|13 97|61 97|47 75|29 61|13 75|53 29|13 97|29 53|29 61|53");
+        let order = PageOrder::new(&rules).unwrap();
+        let map = rule_map(&rules);
+        for a in 0..PAGES {
+            for b in 0..PAGES {
+                assert_eq!(order.must_precede(a, b), map.must_precede(a, b));
+            }
+        }
+        let update = [97, 13, 75, 29, 47, 61];
+        assert_eq!(explain(&update, &order), explain(&update, &map));
+        assert_eq!(repair(&update, &order), repair(&update, &map));
+        assert_eq!(order.successors(61).collect::<Vec<_>>(), vec![13, 53]);
+        assert!(!order.must_precede(47, 1000));
+    }
+
+    #[test]
+    fn closure_follows_chains_and_loops() {
+        let order = PageOrder::new(&rules("1|2 2|3 3|4 7|8 8|7")).unwrap();
+        let closure = order.closure();
+        assert!(!order.must_precede(1, 4));
+        assert!(closure.must_precede(1, 4));
+        assert!(!closure.must_precede(4, 1));
+        assert!(closure.must_precede(7, 7));
+        assert!(!closure.must_precede(1, 7));
+
+        let e = PageOrder::new(&rules("1|2 5|100")).unwrap_err();
+        assert_eq!(e.rule.line, 2);
+    }
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+
+// before|after from line of the rule file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub before: usize,
+    pub after: usize,
+    pub line: usize,
+}
+
+// Anything that can tell whether a rule puts one page before another
+pub trait Precedence {
+    fn must_precede(&self, before: usize, after: usize) -> bool;
+}
+
+// Each page mapped to the pages that have to come before it
+impl Precedence for HashMap<usize, Vec<usize>> {
+    fn must_precede(&self, before: usize, after: usize) -> bool {
+        self.get(&after)
+            .is_some_and(|pages| pages.contains(&before))
+    }
+}
+
+// Maps each page to the pages that have to come before it, each only once
+pub fn rule_map(rules: &[Rule]) -> HashMap<usize, Vec<usize>> {
+    let mut map: HashMap<usize, Vec<usize>> = HashMap::new();
+    for rule in rules {
+        let before = map.entry(rule.after).or_default();
+        if !before.contains(&rule.before) {
+            before.push(rule.before);
+        }
+    }
+    map
+}
+
+// A rule before|after broken by an update, positions in the update start from 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub before: usize,
+    pub after: usize,
+    // after comes first in the update, so after_position < before_position
+    pub before_position: usize,
+    pub after_position: usize,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}|{} but {} is at {} and {} at {}",
+            self.before,
+            self.after,
+            self.after,
+            self.after_position,
+            self.before,
+            self.before_position
+        )
+    }
+}
+
+// Every rule the update breaks, ordered by where the page that should come later is.
+// An update is in order when this is empty
+pub fn explain(update: &[usize], rules: &impl Precedence) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    for (after_position, &after) in update.iter().enumerate() {
+        for (before_position, &before) in update.iter().enumerate().skip(after_position + 1) {
+            if rules.must_precede(before, after) {
+                violations.push(Violation {
+                    before,
+                    after,
+                    before_position,
+                    after_position,
+                });
+            }
+        }
+    }
+    violations
+}
+
+// Same as explain(..).is_empty() but stops at the first broken rule
+pub fn in_order(update: &[usize], rules: &impl Precedence) -> bool {
+    update.iter().enumerate().all(|(i, &after)| {
+        update[i + 1..]
+            .iter()
+            .all(|&before| !rules.must_precede(before, after))
+    })
+}
+
+// Pages that the rules order in a loop, each has to come before the next
+// and the last one before the first
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub pages: Vec<usize>,
+}
+
+impl fmt::Display for Cycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, page) in self.pages.iter().enumerate() {
+            let next = self.pages[(i + 1) % self.pages.len()];
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}|{}", page, next)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for Cycle {}
+
+// Puts the pages of an update in an order that breaks none of the rules between them.
+// Topological sort where pages that are free to go keep their order from the update,
+// so a valid update comes back as it was.
+// Fails with the cycle if the rules between the pages contradict each other
+pub fn repair(update: &[usize], rules: &impl Precedence) -> Result<Vec<usize>, Cycle> {
+    let n = update.len();
+    // before[j] holds the positions of the pages that have to come before update[j],
+    // after[i] the positions of the pages that wait on update[i]
+    let mut before: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut after: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for i in 0..n {
+        for j in 0..n {
+            if rules.must_precede(update[i], update[j]) {
+                before[j].push(i);
+                after[i].push(j);
+            }
+        }
+    }
+
+    let mut waiting: Vec<usize> = before.iter().map(|b| b.len()).collect();
+    let mut placed = vec![false; n];
+    let mut repaired = Vec::with_capacity(n);
+    while repaired.len() < n {
+        let Some(next) = (0..n).find(|&j| !placed[j] && waiting[j] == 0) else {
+            return Err(find_cycle(update, &before, &placed));
+        };
+        placed[next] = true;
+        repaired.push(update[next]);
+        for &j in &after[next] {
+            waiting[j] -= 1;
+        }
+    }
+    Ok(repaired)
+}
+
+// Every page left unplaced waits on another unplaced page,
+// so walking back through those has to run into itself
+fn find_cycle(update: &[usize], before: &[Vec<usize>], placed: &[bool]) -> Cycle {
+    let mut at = (0..update.len())
+        .find(|&j| !placed[j])
+        .expect("Nothing left to place");
+    let mut path: Vec<usize> = Vec::new();
+    while !path.contains(&at) {
+        path.push(at);
+        at = *before[at]
+            .iter()
+            .find(|&&i| !placed[i])
+            .expect("Page is not waiting on anything");
+    }
+    let start = path.iter().position(|&j| j == at).unwrap_or(0);
+    // The path runs backwards, from a page to one before it
+    let pages = path[start..].iter().rev().map(|&j| update[j]).collect();
+    Cycle { pages }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: &str = "47|53 97|13 97|61 97|47 75|29 61|13 75|53 29|13 97|29 53|29 \
+                         61|53 97|53 61|29 47|13 75|47 97|75 47|61 75|61 47|29 75|13 53|13";
+
+    fn rules(text: &str) -> HashMap<usize, Vec<usize>> {
+        let mut rules: HashMap<usize, Vec<usize>> = HashMap::new();
+        for rule in text.split_whitespace() {
+            let (a, b) = rule.split_once('|').unwrap();
+            rules
+                .entry(b.parse().unwrap())
+                .or_default()
+                .push(a.parse().unwrap());
+        }
+        rules
+    }
+
+    fn permutations(pages: &[usize]) -> Vec<Vec<usize>> {
+        if pages.len() <= 1 {
+            return vec![pages.to_vec()];
+        }
+        let mut all = Vec::new();
+        for i in 0..pages.len() {
+            let mut rest = pages.to_vec();
+            let first = rest.remove(i);
+            for mut tail in permutations(&rest) {
+                tail.insert(0, first);
+                all.push(tail);
+            }
+        }
+        all
+    }
+
+    // Every rule between two pages of the update holds
+    fn obeys(update: &[usize], rules: &HashMap<usize, Vec<usize>>) -> bool {
+        update.iter().enumerate().all(|(j, page)| {
+            rules
+                .get(page)
+                .is_none_or(|before| before.iter().all(|b| !update[j + 1..].contains(b)))
+        })
+    }
+
+    #[test]
+    fn repairs_the_example() {
+        let rules = rules(RULES);
+        assert_eq!(
+            repair(&[75, 97, 47, 61, 53], &rules),
+            Ok(vec![97, 75, 47, 61, 53])
+        );
+        assert_eq!(repair(&[61, 13, 29], &rules), Ok(vec![61, 29, 13]));
+        assert_eq!(
+            repair(&[97, 13, 75, 29, 47], &rules),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+        // Already in order
+        assert_eq!(
+            repair(&[75, 47, 61, 53, 29], &rules),
+            Ok(vec![75, 47, 61, 53, 29])
+        );
+    }
+
+    #[test]
+    fn repaired_order_obeys_every_rule() {
+        let rules = rules(RULES);
+        let pages = [97, 75, 47, 61, 53, 29, 13];
+        // Every ordering of every choice of pages, bits picks the pages
+        for bits in 1..(1 << pages.len()) {
+            let chosen: Vec<usize> = (0..pages.len())
+                .filter(|i| bits & (1 << i) != 0)
+                .map(|i| pages[i])
+                .collect();
+            for update in permutations(&chosen) {
+                let repaired = repair(&update, &rules).unwrap();
+                assert!(obeys(&repaired, &rules), "{:?} -> {:?}", update, repaired);
+                let mut sorted = repaired.clone();
+                sorted.sort();
+                let mut expected = chosen.clone();
+                expected.sort();
+                assert_eq!(sorted, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn explains_the_example() {
+        let rules = rules(RULES);
+        assert_eq!(explain(&[75, 47, 61, 53, 29], &rules), vec![]);
+        assert_eq!(
+            explain(&[75, 97, 47, 61, 53], &rules),
+            vec![Violation {
+                before: 97,
+                after: 75,
+                before_position: 1,
+                after_position: 0
+            }]
+        );
+        let broken: Vec<(usize, usize)> = explain(&[97, 13, 75, 29, 47], &rules)
+            .iter()
+            .map(|v| (v.before, v.after))
+            .collect();
+        assert_eq!(broken, vec![(75, 13), (29, 13), (47, 13), (47, 29)]);
+        for update in [[61, 13, 29], [13, 29, 61], [29, 61, 13]] {
+            assert_eq!(in_order(&update, &rules), obeys(&update, &rules));
+        }
+    }
+
+    #[test]
+    fn pages_without_rules_keep_their_place() {
+        let rules = rules("1|2");
+        assert_eq!(repair(&[5, 2, 4, 1, 3], &rules), Ok(vec![5, 4, 1, 2, 3]));
+    }
+
+    #[test]
+    fn contradicting_rules_are_reported() {
+        let looping = rules("47|53 53|29 29|47 29|13 61|13");
+        let cycle = repair(&[61, 13, 29, 53, 47], &looping).unwrap_err();
+        assert_eq!(cycle.pages.len(), 3);
+        for (i, page) in cycle.pages.iter().enumerate() {
+            let next = cycle.pages[(i + 1) % cycle.pages.len()];
+            assert!(looping[&next].contains(page));
+        }
+        let mut pages = cycle.pages.clone();
+        pages.sort();
+        assert_eq!(pages, vec![29, 47, 53]);
+
+        // A page can not come before itself
+        let itself = rules("7|7");
+        assert_eq!(repair(&[4, 7], &itself), Err(Cycle { pages: vec![7] }));
+        assert_eq!(
+            Cycle {
+                pages: vec![47, 53, 29]
+            }
+            .to_string(),
+            "47|53, 53|29, 29|47"
+        );
+    }
+}
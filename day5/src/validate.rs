@@ -0,0 +1,275 @@
+use crate::rules::{Cycle, Rule};
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    // Line numbers start from 1
+    Duplicate {
+        rule: Rule,
+        first: usize,
+    },
+    SelfReference {
+        rule: Rule,
+    },
+    // Page in an update that no rule mentions, update is the first one it is in
+    // counting from 1
+    Unruled {
+        page: usize,
+        update: usize,
+    },
+    // Pages that all have to come before each other, with the shortest loop through
+    // each rule among them
+    Cycle {
+        group: Vec<usize>,
+        cycles: Vec<Cycle>,
+    },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Duplicate { rule, first } => write!(
+                f,
+                "line {}: {}|{} repeats line {}",
+                rule.line, rule.before, rule.after, first
+            ),
+            Problem::SelfReference { rule } => write!(
+                f,
+                "line {}: {}|{} puts a page before itself",
+                rule.line, rule.before, rule.after
+            ),
+            Problem::Unruled { page, update } => {
+                write!(f, "update {}: page {} is in no rule", update, page)
+            }
+            Problem::Cycle { group, cycles } => {
+                let pages: Vec<String> = group.iter().map(|p| p.to_string()).collect();
+                let cycles: Vec<String> = cycles.iter().map(|c| c.to_string()).collect();
+                write!(
+                    f,
+                    "rules loop among {}: {}",
+                    pages.join(", "),
+                    cycles.join("; ")
+                )
+            }
+        }
+    }
+}
+
+// Everything suspicious about a set of rules and the updates they are used on.
+// Cycles are reported once for each group of pages that all come before each other,
+// with the shortest loop through every rule in the group, each loop only once
+pub fn validate(rules: &[Rule], updates: &[Vec<usize>]) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    let mut first_seen: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+    for rule in rules {
+        if rule.before == rule.after {
+            problems.push(Problem::SelfReference { rule: *rule });
+        }
+        match first_seen.get(&(rule.before, rule.after)) {
+            Some(&first) => problems.push(Problem::Duplicate { rule: *rule, first }),
+            None => {
+                first_seen.insert((rule.before, rule.after), rule.line);
+            }
+        }
+    }
+
+    let mentioned: BTreeSet<usize> = rules.iter().flat_map(|r| [r.before, r.after]).collect();
+    let mut reported = BTreeSet::new();
+    for (i, update) in updates.iter().enumerate() {
+        for &page in update {
+            if !mentioned.contains(&page) && reported.insert(page) {
+                problems.push(Problem::Unruled {
+                    page,
+                    update: i + 1,
+                });
+            }
+        }
+    }
+
+    // Pages to the pages that have to come after them, self references are
+    // already reported so they are left out here
+    let mut after: BTreeMap<usize, BTreeSet<usize>> = BTreeMap::new();
+    for rule in rules.iter().filter(|r| r.before != r.after) {
+        after.entry(rule.before).or_default().insert(rule.after);
+        after.entry(rule.after).or_default();
+    }
+    for (group, cycles) in cycles(&after) {
+        problems.push(Problem::Cycle { group, cycles });
+    }
+    problems
+}
+
+// Each strongly connected group of pages with the shortest loops through its rules
+fn cycles(after: &BTreeMap<usize, BTreeSet<usize>>) -> Vec<(Vec<usize>, Vec<Cycle>)> {
+    let reach: BTreeMap<usize, BTreeSet<usize>> = after
+        .keys()
+        .map(|&page| (page, reachable(after, page)))
+        .collect();
+
+    let mut grouped = BTreeSet::new();
+    let mut cycles = Vec::new();
+    for &page in after.keys() {
+        if grouped.contains(&page) {
+            continue;
+        }
+        let group: BTreeSet<usize> = reach[&page]
+            .iter()
+            .copied()
+            .filter(|other| reach[other].contains(&page))
+            .collect();
+        if group.is_empty() {
+            continue;
+        }
+        grouped.extend(group.iter().copied());
+
+        // A rule a|b inside the group closes a loop with the shortest way back from b to a
+        let mut seen = BTreeSet::new();
+        let mut loops = Vec::new();
+        for &before in &group {
+            for &next in after[&before].iter().filter(|p| group.contains(p)) {
+                let Some(back) = shortest_path(after, next, before) else {
+                    continue;
+                };
+                let mut pages = vec![before];
+                pages.extend(&back[..back.len() - 1]);
+                // Start from the lowest page so the same loop found from another rule
+                // looks the same
+                let lowest = (0..pages.len()).min_by_key(|&i| pages[i]).unwrap_or(0);
+                pages.rotate_left(lowest);
+                if seen.insert(pages.clone()) {
+                    loops.push(Cycle { pages });
+                }
+            }
+        }
+        cycles.push((group.into_iter().collect(), loops));
+    }
+    cycles
+}
+
+// Every page that has to come after page, directly or not
+fn reachable(after: &BTreeMap<usize, BTreeSet<usize>>, page: usize) -> BTreeSet<usize> {
+    let mut seen = BTreeSet::new();
+    let mut queue: VecDeque<usize> = after[&page].iter().copied().collect();
+    while let Some(next) = queue.pop_front() {
+        if seen.insert(next) {
+            queue.extend(after[&next].iter().copied());
+        }
+    }
+    seen
+}
+
+// Breadth first from start to end, both included, None if end can not be reached
+fn shortest_path(
+    after: &BTreeMap<usize, BTreeSet<usize>>,
+    start: usize,
+    end: usize,
+) -> Option<Vec<usize>> {
+    let mut from: BTreeMap<usize, usize> = BTreeMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(page) = queue.pop_front() {
+        if page == end {
+            let mut pages = vec![page];
+            while let Some(&previous) = from.get(pages.last()?) {
+                pages.push(previous);
+            }
+            pages.reverse();
+            return Some(pages);
+        }
+        for &next in after[&page].iter() {
+            if next != start {
+                if let Entry::Vacant(entry) = from.entry(next) {
+                    entry.insert(page);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(text: &str) -> Vec<Rule> {
+        text.split_whitespace()
+            .enumerate()
+            .map(|(i, rule)| {
+                let (before, after) = rule.split_once('|').unwrap();
+                Rule {
+                    before: before.parse().unwrap(),
+                    after: after.parse().unwrap(),
+                    line: i + 1,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_each_kind_of_problem() {
+        let rules = rules("47|53 53|29 29|47 47|53 5|5 29|13 13|61 61|29 53|61");
+        let problems = validate(&rules, &[vec![47, 53, 29], vec![13, 77, 29, 77]]);
+        assert_eq!(
+            problems,
+            vec![
+                Problem::Duplicate {
+                    rule: rules[3],
+                    first: 1
+                },
+                Problem::SelfReference { rule: rules[4] },
+                Problem::Unruled {
+                    page: 77,
+                    update: 2
+                },
+                Problem::Cycle {
+                    group: vec![13, 29, 47, 53, 61],
+                    cycles: vec![
+                        Cycle {
+                            pages: vec![13, 61, 29]
+                        },
+                        Cycle {
+                            pages: vec![29, 47, 53]
+                        },
+                        Cycle {
+                            pages: vec![29, 47, 53, 61]
+                        },
+                    ]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn every_rule_in_a_loop_is_covered() {
+        // Two loops that only share page 1 and a third one through both
+        let rules = rules("1|2 2|3 3|1 1|4 4|5 5|1 3|4 4|2");
+        let Some(Problem::Cycle { group, cycles }) = validate(&rules, &[]).pop() else {
+            panic!("No cycle found");
+        };
+        assert_eq!(group, vec![1, 2, 3, 4, 5]);
+        for rule in &rules {
+            assert!(
+                cycles.iter().any(|c| c
+                    .to_string()
+                    .contains(&format!("{}|{}", rule.before, rule.after))),
+                "{}|{} is in no reported loop",
+                rule.before,
+                rule.after
+            );
+        }
+        assert_eq!(
+            Problem::Cycle { group, cycles }.to_string(),
+            "rules loop among 1, 2, 3, 4, 5: 1|2, 2|3, 3|1; \
+             1|4, 4|5, 5|1; 2|3, 3|4, 4|2"
+        );
+    }
+
+    #[test]
+    fn ordered_rules_have_no_cycles() {
+        let rules = rules("1|2 2|3 1|3 3|4");
+        assert_eq!(validate(&rules, &[vec![1, 2, 3, 4]]), vec![]);
+    }
+}
@@ -1,79 +1,12 @@
-use std::collections::HashSet;
+use bitgrid::BitGrid;
+use day6::matrix::Matrix;
+use render::{Colour, Overlay, Style};
 use std::env;
-use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::ops::{Div, Rem};
+use std::ops::Div;
 use std::path::Path;
 
-#[derive(Clone, Debug, Default)]
-pub struct Matrix<T> {
-    cols: usize,
-    data: Vec<T>,
-}
-
-impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
-    pub fn new(cols: usize, data: Vec<T>) -> Self {
-        Self { cols, data }
-    }
-
-    pub fn get(&self, x: isize, y: isize) -> Option<T> {
-        if x >= 0
-            && y >= 0
-            && x < self.cols.try_into().unwrap()
-            && y < self.data.len().div(self.cols).try_into().unwrap()
-        {
-            let pos = self.pos_from_coords(x.try_into().unwrap(), y.try_into().unwrap());
-            if pos >= self.data.len() {
-                return None;
-            }
-            return Some(self.data[pos]);
-        }
-        None
-    }
-
-    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
-        let asd = &self.cols * y + x;
-        //println!("asd: {}", &asd);
-        return asd;
-    }
-
-    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
-        let x = pos.rem(&self.cols);
-        let y = pos.div(&self.cols);
-        return (x, y);
-    }
-
-    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
-        let x = pos.rem(&self.cols) as isize;
-        let y = pos.div(&self.cols) as isize;
-        return (x, y);
-    }
-}
-
-impl fmt::Display for Matrix<char> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 && i >= self.cols {
-                write!(f, "\n")?;
-            }
-            write!(f, "{} ", c)?;
-        }
-        Ok(())
-    }
-}
-
-impl Matrix<char> {
-    fn dbg(&self) -> () {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 {
-                println!("");
-            }
-            print!("{} ", c);
-        }
-    }
-}
-
 #[derive(Debug)]
 struct Step {
     pos: usize,
@@ -92,22 +25,13 @@ impl Step {
         }
     }
 
-    fn turn(&mut self) -> () {
+    fn turn(&mut self) {
         match self.dir {
             3 => self.dir = 0,
             _ => self.dir += 1,
         }
     }
 
-    fn dir(&self) -> &str {
-        match self.dir {
-            0 => "up",
-            1 => "right",
-            2 => "down",
-            3 => "left",
-            _ => "ananas",
-        }
-    }
     fn path(&self) -> char {
         match self.dir {
             0 => '|',
@@ -136,7 +60,8 @@ where
     Ok(io::BufReader::new(file).lines())
 }
 
-fn next(map: &mut Matrix<char>, step: &mut Step, path: &mut HashSet<(usize, usize)>) -> usize {
+// path has a cell for every position and direction, x is the position and y the direction
+fn next(map: &mut Matrix<char>, step: &mut Step, path: &mut BitGrid) -> usize {
     let (cur_x, cur_y) = map.icoords_from_pos(step.pos);
     let (dx, dy) = step.advance();
     let next_x = cur_x + dx;
@@ -145,20 +70,16 @@ fn next(map: &mut Matrix<char>, step: &mut Step, path: &mut HashSet<(usize, usiz
     // We are currently in this step
     map.data[step.pos] = step.dirsym();
 
-    //map.dbg();
     if check_loop(step, path) {
-        let coords = &map.icoords_from_pos(step.pos);
-        //map.dbg();
         return 0;
     }
 
     match map.get(next_x, next_y) {
-        Some(c) if c == '#' => {
+        Some('#') => {
             step.turn();
             map.data[step.pos] = '+';
             //path.insert((step.pos, step.dir));
-            let step_count = next(map, step, path);
-            return step_count;
+            next(map, step, path)
         }
         Some(_) => {
             let mut next_step = Step {
@@ -167,36 +88,70 @@ fn next(map: &mut Matrix<char>, step: &mut Step, path: &mut HashSet<(usize, usiz
                 count: step.count + 1,
             };
             map.data[step.pos] = step.path();
-            path.insert((step.pos, step.dir));
-            let step_count = next(map, &mut next_step, path);
-            return step_count;
+            path.set(step.pos, step.dir);
+            next(map, &mut next_step, path)
         }
         None => {
             map.data[step.pos] = step.path();
-            path.insert((step.pos, step.dir));
-            return step.count;
+            path.set(step.pos, step.dir);
+            step.count
         }
-    };
+    }
 }
 
-fn check_loop(step: &mut Step, path: &HashSet<(usize, usize)>) -> bool {
-    match path.get(&(step.pos, step.dir)) {
-        Some(_) => {
-            //println!("loop");
-            return true;
+fn check_loop(step: &mut Step, path: &BitGrid) -> bool {
+    path.test(step.pos, step.dir)
+}
+
+// Cells the guard walks through in order, for drawing. Stops where the guard
+// leaves the map or comes back to a cell facing the same way
+fn route(map: &Matrix<char>, guard: usize) -> Vec<usize> {
+    let mut step = Step {
+        pos: guard,
+        dir: 0,
+        count: 0,
+    };
+    let mut seen = BitGrid::new(map.data.len(), 4);
+    let mut route = vec![guard];
+    while !seen.test(step.pos, step.dir) {
+        seen.set(step.pos, step.dir);
+        let (x, y) = map.icoords_from_pos(step.pos);
+        let (dx, dy) = step.advance();
+        match map.get(x + dx, y + dy) {
+            Some('#') => step.turn(),
+            Some(_) => {
+                step.pos = map.pos_from_coords((x + dx) as usize, (y + dy) as usize);
+                route.push(step.pos);
+            }
+            None => break,
         }
-        None => return false,
     }
+    route
+}
+
+// Obstacles in red and the guard's route as arrows, starting from the bracketed cell
+fn show(puzzle: &Matrix<char>, guard: usize) {
+    let obstacles = (0..puzzle.data.len()).filter(|&pos| puzzle.data[pos] == '#');
+    let overlay = Overlay::new(puzzle)
+        .highlight(obstacles, Style::default().colour(Colour::Red))
+        .path(
+            &route(puzzle, guard),
+            Style::default().colour(Colour::Yellow),
+        )
+        .highlight([guard], Style::default().brackets('[', ']'))
+        .rulers();
+    print!("{}", overlay);
 }
 
 fn main() {
     let args: Vec<String> = env::args().collect();
     let filename = &args[1];
+    let show_grid = args.iter().any(|arg| arg == "--show");
     let mut data = Vec::new();
     let mut rows: usize = 0;
 
     if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
+        for line in lines.map_while(Result::ok) {
             for c in line.chars() {
                 data.push(c);
             }
@@ -210,26 +165,27 @@ fn main() {
     let mut p1puzzle = puzzle.clone();
 
     let guard = puzzle.data.iter().position(|&c| c == '^').unwrap();
+    if show_grid {
+        show(&puzzle, guard);
+    }
     let mut start = Step {
         pos: guard,
         dir: 0,
         count: 0,
     };
 
-    let mut p1path: HashSet<(usize, usize)> = HashSet::new();
+    let mut p1path = BitGrid::new(puzzle.data.len(), 4);
     next(&mut p1puzzle, &mut start, &mut p1path);
 
-    let count = p1puzzle
-        .data
-        .iter()
-        .fold(0, |sum, &e| if e == 'X' { sum + 1 } else { sum + 0 });
+    let count = p1puzzle.data.iter().filter(|&&e| e == 'X').count();
     println!("P1: {:?}", count);
 
     let mut p2count: usize = 0;
+    let mut path = BitGrid::new(puzzle.data.len(), 4);
 
     for (i, point) in puzzle.data.iter().enumerate() {
         println!("i: {}", i);
-        let mut path: HashSet<(usize, usize)> = HashSet::new();
+        path.clear_all();
 
         if *point == '.' {
             let mut pusle = puzzle.clone();
@@ -0,0 +1,69 @@
+use std::fmt;
+use std::ops::{Div, Rem};
+
+#[derive(Clone, Debug, Default)]
+pub struct Matrix<T> {
+    pub cols: usize,
+    pub data: Vec<T>,
+}
+
+impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
+    pub fn new(cols: usize, data: Vec<T>) -> Self {
+        Self { cols, data }
+    }
+
+    pub fn get(&self, x: isize, y: isize) -> Option<T> {
+        if x >= 0
+            && y >= 0
+            && x < self.cols.try_into().unwrap()
+            && y < self.data.len().div(self.cols).try_into().unwrap()
+        {
+            let pos = self.pos_from_coords(x.try_into().unwrap(), y.try_into().unwrap());
+            if pos >= self.data.len() {
+                return None;
+            }
+            return Some(self.data[pos]);
+        }
+        None
+    }
+
+    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
+        self.cols * y + x
+    }
+
+    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
+        let x = pos.rem(self.cols);
+        let y = pos.div(self.cols);
+        (x, y)
+    }
+
+    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
+        let x = pos.rem(self.cols) as isize;
+        let y = pos.div(self.cols) as isize;
+        (x, y)
+    }
+}
+
+impl fmt::Display for Matrix<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.data.iter().enumerate() {
+            if i.rem(self.cols) == 0 && i >= self.cols {
+                writeln!(f)?;
+            }
+            write!(f, "{} ", c)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: std::fmt::Display> render::Grid for Matrix<T> {
+    type Cell = T;
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn cells(&self) -> &[T] {
+        &self.data
+    }
+}
@@ -1,17 +1,13 @@
+use day8::matrix::Matrix;
+use render::{Colour, Overlay, Style};
 use std::collections::HashMap;
 use std::env;
 use std::fmt;
 use std::fs::File;
 use std::io::{self, BufRead};
-use std::ops::{Div, Rem};
+use std::ops::Div;
 use std::path::Path;
 
-#[derive(Clone, Debug, Default)]
-struct Matrix<T> {
-    cols: usize,
-    data: Vec<T>,
-}
-
 #[derive(Clone, Copy, Debug, Default)]
 struct Point {
     antenna: char,
@@ -25,94 +21,6 @@ impl fmt::Display for Point {
     }
 }
 
-impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
-    pub fn new(cols: usize, data: Vec<T>) -> Self {
-        Self { cols, data }
-    }
-
-    pub fn get_mut(&mut self, pos: isize) -> Option<&mut T> {
-        if pos >= 0 && pos < self.data.len() as isize {
-            return Some(&mut self.data[pos as usize]);
-        } else {
-            return None;
-        }
-    }
-
-    pub fn get(&mut self, x: isize, y: isize) -> Option<&mut T> {
-        if self.check_boundaries(x, y) {
-            let pos = self.pos_from_coords(x.try_into().unwrap(), y.try_into().unwrap());
-            if pos >= self.data.len() {
-                return None;
-            }
-            return Some(&mut self.data[pos]);
-        }
-        None
-    }
-
-    fn check_boundaries(&self, x: isize, y: isize) -> bool {
-        if x >= 0
-            && y >= 0
-            && x < self.cols.try_into().unwrap()
-            && y < self.data.len().div(self.cols).try_into().unwrap()
-        {
-            return true;
-        } else {
-            return false;
-        }
-    }
-
-    //pub fn get_antinode(&self, x: us
-
-    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
-        let asd = &self.cols * y + x;
-        //println!("asd: {}", &asd);
-        return asd;
-    }
-
-    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
-        let x = pos.rem(&self.cols);
-        let y = pos.div(&self.cols);
-        return (x, y);
-    }
-
-    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
-        let x = pos.rem(&self.cols) as isize;
-        let y = pos.div(&self.cols) as isize;
-        return (x, y);
-    }
-}
-
-impl fmt::Display for Matrix<Point> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 && i >= self.cols {
-                write!(f, "\n")?;
-            }
-            write!(f, "{} ", c)?;
-        }
-        Ok(())
-    }
-}
-
-impl Matrix<Point> {
-    fn dbg(&self) -> () {
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 {
-                println!("");
-            }
-            print!("{} ", c);
-        }
-        println!("");
-        for (i, c) in self.data.iter().enumerate() {
-            if i.rem(self.cols) == 0 {
-                println!("");
-            }
-            print!("{} ", c.antinodes);
-        }
-        println!("\n");
-    }
-}
-
 fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
 where
     P: AsRef<Path>,
@@ -132,7 +40,6 @@ fn map_antennas(puzzle: &mut Matrix<Point>) -> HashMap<char, Vec<usize>> {
                 }
                 None => {
                     antenna_map.insert(point.antenna, vec![i]);
-                    ()
                 }
             }
         }
@@ -140,9 +47,9 @@ fn map_antennas(puzzle: &mut Matrix<Point>) -> HashMap<char, Vec<usize>> {
     antenna_map
 }
 
-fn check_antinodes(puzzle: &mut Matrix<Point>, antennas: &Vec<usize>) -> () {
+fn check_antinodes(puzzle: &mut Matrix<Point>, antennas: &[usize]) {
     if antennas.len() < 2 {
-        return ();
+        return;
     }
 
     for (i, _antenna1) in antennas.iter().enumerate() {
@@ -151,10 +58,9 @@ fn check_antinodes(puzzle: &mut Matrix<Point>, antennas: &Vec<usize>) -> () {
             check(puzzle, antenna_pair);
         }
     }
-    ()
 }
 
-fn check(puzzle: &mut Matrix<Point>, pair: (&usize, &usize)) -> () {
+fn check(puzzle: &mut Matrix<Point>, pair: (&usize, &usize)) {
     let (x1, y1) = puzzle.icoords_from_pos(*pair.0);
     let (x2, y2) = puzzle.icoords_from_pos(*pair.1);
     let dx = x2 - x1;
@@ -177,13 +83,11 @@ fn check(puzzle: &mut Matrix<Point>, pair: (&usize, &usize)) -> () {
             pos.antinodes += 1;
         }
     }
-
-    ()
 }
 
-fn check_antinodes_recursive(puzzle: &mut Matrix<Point>, antennas: &Vec<usize>) -> () {
+fn check_antinodes_recursive(puzzle: &mut Matrix<Point>, antennas: &[usize]) {
     if antennas.len() < 2 {
-        return ();
+        return;
     }
 
     for (i, _antenna1) in antennas.iter().enumerate() {
@@ -202,7 +106,6 @@ fn check_antinodes_recursive(puzzle: &mut Matrix<Point>, antennas: &Vec<usize>)
             check_recursive(puzzle, antenna_brand, x2, dx, y2, dy);
         }
     }
-    ()
 }
 
 fn check_recursive(
@@ -212,29 +115,48 @@ fn check_recursive(
     dx: isize,
     y: isize,
     dy: isize,
-) -> () {
-    match puzzle.get(x + dx, y + dy) {
-        Some(pos) => {
-            if pos.antenna != antenna {
-                pos.antinodes += 1;
-                let new_x = x + dx;
-                let new_y = y + dy;
-                check_recursive(puzzle, antenna, new_x, dx, new_y, dy);
-            };
-            ()
-        }
-        _ => (),
+) {
+    if let Some(pos) = puzzle.get(x + dx, y + dy) {
+        if pos.antenna != antenna {
+            pos.antinodes += 1;
+            let new_x = x + dx;
+            let new_y = y + dy;
+            check_recursive(puzzle, antenna, new_x, dx, new_y, dy);
+        };
     }
 }
 
+// Antinodes are drawn as # the way the puzzle text does, or bracketed
+// when they land on top of another antenna
+fn show(puzzle: &Matrix<Point>) {
+    let cells = |antinode_on_antenna: bool| {
+        (0..puzzle.data.len()).filter(move |&pos| {
+            let point = puzzle.data[pos];
+            point.antinodes > 0 && (point.antenna != '.') == antinode_on_antenna
+        })
+    };
+    let antennas = (0..puzzle.data.len()).filter(|&pos| puzzle.data[pos].antenna != '.');
+
+    let overlay = Overlay::new(puzzle)
+        .highlight(antennas, Style::default().colour(Colour::Cyan))
+        .highlight(
+            cells(false),
+            Style::default().glyph('#').colour(Colour::Yellow),
+        )
+        .highlight(cells(true), Style::default().brackets('[', ']'))
+        .rulers();
+    print!("{}", overlay);
+}
+
 fn main() {
     let args: Vec<String> = env::args().collect();
     let filename = &args[1];
+    let show_grid = args.iter().any(|arg| arg == "--show");
     let mut data = Vec::new();
     let mut rows: usize = 0;
 
     if let Ok(lines) = read_lines(filename) {
-        for line in lines.flatten() {
+        for line in lines.map_while(Result::ok) {
             for c in line.chars() {
                 data.push(Point {
                     antenna: c,
@@ -265,6 +187,9 @@ fn main() {
         .filter(|x| **x > 0)
         .count();
 
+    if show_grid {
+        show(&puzzle);
+    }
     println!("P1: {:?}", p1_sum);
 
     for (_key, value) in map2.into_iter() {
@@ -277,5 +202,8 @@ fn main() {
         .filter(|x| x.antinodes > 0 || x.antenna != '.')
         .count();
 
+    if show_grid {
+        show(&p2puzzle);
+    }
     println!("P2: {:?}", p2_sum);
 }
@@ -0,0 +1,68 @@
+use std::ops::{Div, Rem};
+
+#[derive(Clone, Debug, Default)]
+pub struct Matrix<T> {
+    pub cols: usize,
+    pub data: Vec<T>,
+}
+
+impl<T: Default + Copy + std::fmt::Display> Matrix<T> {
+    pub fn new(cols: usize, data: Vec<T>) -> Self {
+        Self { cols, data }
+    }
+
+    pub fn rows(&self) -> usize {
+        if self.cols == 0 {
+            return 0;
+        }
+        self.data.len().div(self.cols)
+    }
+
+    pub fn get_mut(&mut self, pos: isize) -> Option<&mut T> {
+        if pos >= 0 && pos < self.data.len() as isize {
+            Some(&mut self.data[pos as usize])
+        } else {
+            None
+        }
+    }
+
+    pub fn get(&mut self, x: isize, y: isize) -> Option<&mut T> {
+        if self.check_boundaries(x, y) {
+            let pos = self.pos_from_coords(x as usize, y as usize);
+            return self.data.get_mut(pos);
+        }
+        None
+    }
+
+    pub fn check_boundaries(&self, x: isize, y: isize) -> bool {
+        x >= 0 && y >= 0 && x < self.cols as isize && y < self.rows() as isize
+    }
+
+    pub fn pos_from_coords(&self, x: usize, y: usize) -> usize {
+        self.cols * y + x
+    }
+
+    pub fn coords_from_pos(&self, pos: usize) -> (usize, usize) {
+        let x = pos.rem(self.cols);
+        let y = pos.div(self.cols);
+        (x, y)
+    }
+
+    pub fn icoords_from_pos(&self, pos: usize) -> (isize, isize) {
+        let x = pos.rem(self.cols) as isize;
+        let y = pos.div(self.cols) as isize;
+        (x, y)
+    }
+}
+
+impl<T: std::fmt::Display> render::Grid for Matrix<T> {
+    type Cell = T;
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn cells(&self) -> &[T] {
+        &self.data
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// What the renderer needs from a grid, every day implements it for its own Matrix
+pub trait Grid {
+    type Cell: fmt::Display;
+
+    fn cols(&self) -> usize;
+
+    // Row by row, cols cells to a row
+    fn cells(&self) -> &[Self::Cell];
+
+    fn rows(&self) -> usize {
+        if self.cols() == 0 {
+            return 0;
+        }
+        self.cells().len() / self.cols()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Colour {
+    fn ansi(&self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+        }
+    }
+}
+
+// How a highlighted cell is drawn, unset fields fall through to the layers below
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Style {
+    pub glyph: Option<char>,
+    pub brackets: Option<(char, char)>,
+    pub colour: Option<Colour>,
+}
+
+impl Style {
+    pub fn glyph(mut self, glyph: char) -> Self {
+        self.glyph = Some(glyph);
+        self
+    }
+
+    pub fn brackets(mut self, left: char, right: char) -> Self {
+        self.brackets = Some((left, right));
+        self
+    }
+
+    pub fn colour(mut self, colour: Colour) -> Self {
+        self.colour = Some(colour);
+        self
+    }
+
+    fn over(self, below: Style) -> Style {
+        Style {
+            glyph: self.glyph.or(below.glyph),
+            brackets: self.brackets.or(below.brackets),
+            colour: self.colour.or(below.colour),
+        }
+    }
+}
+
+// Draws a matrix with any number of layers on top of it
+// Layers are painted in the order they were added, later ones win
+pub struct Overlay<'a, G> {
+    grid: &'a G,
+    layers: Vec<HashMap<usize, Style>>,
+    rulers: bool,
+}
+
+impl<'a, G: Grid> Overlay<'a, G> {
+    pub fn new(grid: &'a G) -> Self {
+        Self {
+            grid,
+            layers: Vec::new(),
+            rulers: false,
+        }
+    }
+
+    pub fn highlight<I>(mut self, cells: I, style: Style) -> Self
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        self.layers
+            .push(cells.into_iter().map(|pos| (pos, style)).collect());
+        self
+    }
+
+    // Draws an arrow on every step of the path pointing to the next one
+    pub fn path(mut self, cells: &[usize], style: Style) -> Self {
+        let mut layer: HashMap<usize, Style> = HashMap::new();
+        for step in cells.windows(2) {
+            let (x1, y1) = self.coords(step[0]);
+            let (x2, y2) = self.coords(step[1]);
+            let arrow = match ((x2 - x1).signum(), (y2 - y1).signum()) {
+                (0, -1) => '^',
+                (0, 1) => 'v',
+                (-1, 0) => '<',
+                (1, 0) => '>',
+                (-1, -1) => '↖',
+                (1, -1) => '↗',
+                (-1, 1) => '↙',
+                (1, 1) => '↘',
+                _ => '*',
+            };
+            layer.insert(step[0], style.glyph(arrow));
+        }
+        if let Some(&last) = cells.last() {
+            layer.entry(last).or_insert(style);
+        }
+        self.layers.push(layer);
+        self
+    }
+
+    // Adds column numbers above and row numbers left of the grid
+    pub fn rulers(mut self) -> Self {
+        self.rulers = true;
+        self
+    }
+
+    fn coords(&self, pos: usize) -> (isize, isize) {
+        let cols = self.grid.cols().max(1);
+        ((pos % cols) as isize, (pos / cols) as isize)
+    }
+
+    fn style(&self, pos: usize) -> Style {
+        self.layers
+            .iter()
+            .filter_map(|layer| layer.get(&pos))
+            .fold(Style::default(), |below, style| style.over(below))
+    }
+}
+
+impl<G: Grid> fmt::Display for Overlay<'_, G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells: Vec<(String, Style)> = (0..self.grid.cells().len())
+            .map(|pos| {
+                let style = self.style(pos);
+                let text = match style.glyph {
+                    Some(glyph) => glyph.to_string(),
+                    None => self.grid.cells()[pos].to_string(),
+                };
+                (text, style)
+            })
+            .collect();
+        let width = cells
+            .iter()
+            .map(|(text, _)| text.chars().count())
+            .max()
+            .unwrap_or(1);
+        let margin = self.grid.rows().saturating_sub(1).to_string().len();
+
+        if self.rulers {
+            write!(f, "{:margin$} ", "")?;
+            for x in 0..self.grid.cols() {
+                write!(f, " {:>width$} ", x % 10)?;
+            }
+            writeln!(f)?;
+        }
+
+        for (y, row) in cells.chunks(self.grid.cols().max(1)).enumerate() {
+            if self.rulers {
+                write!(f, "{:>margin$} ", y)?;
+            }
+            for (text, style) in row {
+                let (left, right) = style.brackets.unwrap_or((' ', ' '));
+                let cell = format!("{}{:>width$}{}", left, text, right);
+                match style.colour {
+                    Some(colour) => write!(f, "\x1b[{}m{}\x1b[0m", colour.ansi(), cell)?,
+                    None => write!(f, "{}", cell)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Text {
+        cols: usize,
+        cells: Vec<char>,
+    }
+
+    impl Grid for Text {
+        type Cell = char;
+
+        fn cols(&self) -> usize {
+            self.cols
+        }
+
+        fn cells(&self) -> &[char] {
+            &self.cells
+        }
+    }
+
+    fn text(rows: &str) -> Text {
+        Text {
+            cols: rows.lines().next().map_or(0, |row| row.chars().count()),
+            cells: rows.lines().flat_map(|row| row.chars()).collect(),
+        }
+    }
+
+    #[test]
+    fn paths_point_to_the_next_step() {
+        let grid = text("abc\ndef\nghi");
+        let path = Overlay::new(&grid).path(&[0, 1, 2, 5, 8, 7, 4], Style::default());
+        assert_eq!(path.to_string(), " >  >  v \n d  e  v \n g  ^  < \n");
+
+        let diagonal = Overlay::new(&grid).path(&[6, 4, 2, 4, 8], Style::default());
+        assert_eq!(diagonal.to_string(), " a  b  ↙ \n d  ↘  f \n ↗  h  i \n");
+    }
+
+    #[test]
+    fn later_layers_win() {
+        let grid = text("ab\ncd");
+        let overlay = Overlay::new(&grid)
+            .path(&[0, 1, 3], Style::default().colour(Colour::Red))
+            .highlight([1], Style::default().glyph('#'))
+            .highlight([3], Style::default().brackets('[', ']'))
+            .rulers();
+        assert_eq!(
+            overlay.to_string(),
+            "   0  1 \n\
+             0 \x1b[31m > \x1b[0m\x1b[31m # \x1b[0m\n\
+             1  c \x1b[31m[d]\x1b[0m\n"
+        );
+    }
+}